@@ -2,9 +2,11 @@ import { useEditorStore } from './store/editorStore';
 import { StartupWindow } from './components/StartupWindow/StartupWindow';
 import { GalleryView } from './components/GalleryView/GalleryView';
 import { Editor } from './components/Editor/Editor';
+import { useImageWatcher } from './hooks/useImageWatcher';
 
 function App() {
   const appPhase = useEditorStore((state) => state.appPhase);
+  useImageWatcher();
 
   switch (appPhase) {
     case 'startup':
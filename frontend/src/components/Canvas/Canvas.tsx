@@ -1,12 +1,206 @@
 import { useRef, useEffect, useCallback } from 'react';
-import { Stage, Layer, Rect, Text, Transformer, Image as KonvaImage } from 'react-konva';
+import { Stage, Layer, Group, Rect, Ellipse, Line, Text, Label, Tag, Transformer, Image as KonvaImage } from 'react-konva';
 import type Konva from 'konva';
-import { useEditorStore } from '../../store/editorStore';
-import type { CanvasObject, TextObject, ImageObject, BackgroundObject, HeroObject } from '../../types/canvas';
+import { useEditorStore, FOCAL_POINT_KEY, parseFocalPoint } from '../../store/editorStore';
+import type {
+  CanvasObject,
+  TextObject,
+  ImageObject,
+  BackgroundObject,
+  HeroObject,
+  ShapeObject,
+  DecorationSettings,
+  ShadowSettings,
+  Gradient,
+} from '../../types/canvas';
 import { ZoomIndicator } from './ZoomIndicator';
 import styles from './Canvas.module.css';
 import useImage from 'use-image';
 
+// Helper: Konva props previewing a layer's drop shadow
+function shadowProps(shadow: ShadowSettings): Record<string, unknown> {
+  if (!shadow.shadowColor) return {};
+  return {
+    shadowColor: shadow.shadowColor,
+    shadowBlur: shadow.shadowBlur ?? 0,
+    shadowOffsetX: shadow.shadowOffsetX ?? 0,
+    shadowOffsetY: shadow.shadowOffsetY ?? 0,
+    shadowOpacity: shadow.shadowOpacity ?? 1,
+  };
+}
+
+// Helper: Konva props previewing an image layer's corner radius, border and shadow
+function decorationProps(decoration: DecorationSettings): Record<string, unknown> {
+  const props: Record<string, unknown> = { cornerRadius: decoration.cornerRadius ?? 0, ...shadowProps(decoration) };
+  if (decoration.stroke && (decoration.strokeWidth ?? 0) > 0) {
+    props.stroke = decoration.stroke;
+    props.strokeWidth = decoration.strokeWidth;
+  }
+  return props;
+}
+
+// Helper: Text layer preview with outline, shadow and background box
+function TextPreview({ text, commonProps }: { text: TextObject; commonProps: Record<string, unknown> }) {
+  const strokeWidth = text.strokeWidth ?? 0;
+  const textProps = {
+    text: text.content,
+    fontSize: text.fontSize,
+    fontFamily: text.fontFamily,
+    fontStyle: `${text.fontWeight} ${text.fontStyle}`,
+    ...(text.fillGradient ? gradientProps(text.fillGradient, text.width, text.height) : { fill: text.fill }),
+    lineHeight: text.lineHeight,
+    // Generation grows the outline outwards, so Konva's centered stroke is doubled and drawn under the fill
+    ...(text.stroke && strokeWidth > 0
+      ? { stroke: text.stroke, strokeWidth: strokeWidth * 2, fillAfterStrokeEnabled: true, lineJoin: 'round' as const }
+      : {}),
+  };
+
+  if (text.backgroundColor) {
+    // The box hugs the text like the generated one; it casts the shadow
+    const padding = text.backgroundPadding ?? 0;
+    const lines = text.content.split('\n').length;
+    const boxHeight = text.fontSize * text.lineHeight * lines + padding * 2;
+    return (
+      <Label {...commonProps}>
+        <Tag fill={text.backgroundColor} cornerRadius={text.cornerRadius ?? boxHeight / 2} {...shadowProps(text)} />
+        <Text {...textProps} padding={padding} />
+      </Label>
+    );
+  }
+
+  return (
+    <Text
+      {...commonProps}
+      {...textProps}
+      {...shadowProps(text)}
+      align={text.align}
+      verticalAlign={text.verticalAlign}
+      width={text.width}
+    />
+  );
+}
+
+// Helper: Konva fill props previewing a gradient over a width×height box
+// (radial gradients are previewed as a circle reaching the farthest corner).
+// `centered` is for nodes whose origin is the box center (Konva ellipses) rather than its top-left corner.
+function gradientProps(gradient: Gradient, width: number, height: number, centered = false): Record<string, unknown> {
+  const ox = centered ? width / 2 : 0;
+  const oy = centered ? height / 2 : 0;
+  const colorStops = [...gradient.stops]
+    .sort((a, b) => a.position - b.position)
+    .flatMap((stop) => [Math.min(Math.max(stop.position, 0), 1), stop.color]);
+  if (gradient.type === 'radial') {
+    const cx = (width * (gradient.centerX ?? 50)) / 100;
+    const cy = (height * (gradient.centerY ?? 50)) / 100;
+    return {
+      fillRadialGradientStartPoint: { x: cx - ox, y: cy - oy },
+      fillRadialGradientEndPoint: { x: cx - ox, y: cy - oy },
+      fillRadialGradientStartRadius: 0,
+      fillRadialGradientEndRadius: Math.hypot(Math.max(cx, width - cx), Math.max(cy, height - cy)),
+      fillRadialGradientColorStops: colorStops,
+    };
+  }
+  // CSS gradient line: through the center, long enough for the corners to get the end colors
+  const angle = ((gradient.angle ?? 180) * Math.PI) / 180;
+  const dx = Math.sin(angle);
+  const dy = -Math.cos(angle);
+  const half = (Math.abs(width * dx) + Math.abs(height * dy)) / 2;
+  return {
+    fillLinearGradientStartPoint: { x: width / 2 - dx * half - ox, y: height / 2 - dy * half - oy },
+    fillLinearGradientEndPoint: { x: width / 2 + dx * half - ox, y: height / 2 + dy * half - oy },
+    fillLinearGradientColorStops: colorStops,
+  };
+}
+
+// Helper: Shape layer preview, drawn in a group whose origin is the layer box's top-left corner
+function ShapePreview({ shape }: { shape: ShapeObject }) {
+  const { width, height } = shape;
+  const strokeWidth = shape.strokeWidth ?? 0;
+  const stroke = shape.stroke && strokeWidth > 0 ? { stroke: shape.stroke, strokeWidth } : {};
+
+  if (shape.shape === 'line') {
+    // Transparent box keeps the whole layer frame clickable and transformable
+    return (
+      <>
+        <Rect width={width} height={height} fill="transparent" />
+        <Line
+          points={[0, height / 2, width, height / 2]}
+          stroke={shape.stroke ?? shape.fill ?? '#000000'}
+          strokeWidth={Math.max(strokeWidth, 1)}
+        />
+      </>
+    );
+  }
+
+  const isEllipse = shape.shape === 'ellipse';
+  const fill = shape.fillGradient ? gradientProps(shape.fillGradient, width, height, isEllipse) : { fill: shape.fill };
+  if (isEllipse) {
+    return <Ellipse x={width / 2} y={height / 2} radiusX={width / 2} radiusY={height / 2} {...fill} {...stroke} />;
+  }
+  return <Rect width={width} height={height} cornerRadius={shape.cornerRadius ?? 0} {...fill} {...stroke} />;
+}
+
+// Outline a mask or clipping base is previewed with, in the layer box's coordinates
+interface Outline {
+  shape: 'rect' | 'ellipse';
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+  cornerRadius: number;
+}
+
+// Helper: Outline of what a clipping group's base covers. The editor clips to the base's outline:
+// rounded image frames, ellipses and line strokes are followed, but transparent pixels of images and the gaps
+// between letters are not (generation clips to the base's actual pixels).
+function baseOutline(base: CanvasObject): Outline {
+  const box: Outline = { shape: 'rect', x: 0, y: 0, width: base.width, height: base.height, cornerRadius: 0 };
+  if (base.type === 'shape') {
+    const shape = base as ShapeObject;
+    if (shape.shape === 'line') {
+      const strokeWidth = Math.max(shape.strokeWidth ?? 0, 1);
+      return { ...box, y: (base.height - strokeWidth) / 2, height: strokeWidth };
+    }
+    return { ...box, shape: shape.shape === 'ellipse' ? 'ellipse' : 'rect', cornerRadius: shape.cornerRadius ?? 0 };
+  }
+  if (base.type === 'hero' || base.type === 'image') {
+    return { ...box, cornerRadius: (base as HeroObject | ImageObject).cornerRadius ?? 0 };
+  }
+  return box;
+}
+
+// Helper: Trace an outline in canvas coordinates, rotated around the layer center like the layer.
+// `reverse` traces it counter-clockwise, so it punches a hole into a clockwise path (inverted masks).
+function traceOutline(ctx: Konva.Context, obj: CanvasObject, outline: Outline, reverse = false) {
+  const { x, y, width: w, height: h } = outline;
+  ctx.save();
+  ctx.translate(obj.x + obj.width / 2, obj.y + obj.height / 2);
+  ctx.rotate((obj.rotation * Math.PI) / 180);
+  ctx.translate(x - obj.width / 2, y - obj.height / 2);
+  if (outline.shape === 'ellipse') {
+    ctx.moveTo(w, h / 2);
+    ctx.ellipse(w / 2, h / 2, w / 2, h / 2, 0, 0, Math.PI * 2, reverse);
+  } else {
+    const r = Math.max(Math.min(outline.cornerRadius, w / 2, h / 2), 0);
+    const corners = reverse
+      ? [[0, 0, 0, h], [0, h, w, h], [w, h, w, 0], [w, 0, 0, 0]]
+      : [[w, 0, w, h], [w, h, 0, h], [0, h, 0, 0], [0, 0, w, 0]];
+    ctx.moveTo(r, 0);
+    corners.forEach(([cx, cy, nx, ny]) => ctx.arcTo(cx, cy, nx, ny, r));
+    ctx.closePath();
+  }
+  ctx.restore();
+}
+
+// Helper: Base layer a layer clips to: the nearest layer below that isn't clipped itself, or the bottom layer
+// (same rule as generation). Undefined for layers that aren't clipped.
+function clipBase(objects: CanvasObject[], index: number): CanvasObject | undefined {
+  if (!objects[index].clipToBelow || index === 0) return undefined;
+  let base = index - 1;
+  while (base > 0 && objects[base].clipToBelow) base--;
+  return objects[base];
+}
+
 // Helper component for loading images
 function CanvasImage({
   src,
@@ -14,12 +208,16 @@ function CanvasImage({
   width,
   height,
   scaleMode,
+  align,
+  focus,
 }: {
   src: string;
   commonProps: Record<string, unknown>;
   width: number;
   height: number;
   scaleMode: 'fill' | 'fit' | 'stretch';
+  align?: { x: number; y: number }; // 0..1 within the box (default: centered, as in generation)
+  focus?: { x: number; y: number } | null; // Fill only: image point (0..1) kept near the box center
 }) {
   const [image] = useImage(src, 'anonymous');
 
@@ -48,11 +246,15 @@ function CanvasImage({
     if (imageRatio > targetRatio) {
       // Image is wider - crop sides
       cropWidth = image.height * targetRatio;
-      cropX = (image.width - cropWidth) / 2;
+      cropX = focus
+        ? Math.min(Math.max(focus.x * image.width - cropWidth / 2, 0), image.width - cropWidth)
+        : (image.width - cropWidth) * (align?.x ?? 0.5);
     } else {
       // Image is taller - crop top/bottom
       cropHeight = image.width / targetRatio;
-      cropY = (image.height - cropHeight) / 2;
+      cropY = focus
+        ? Math.min(Math.max(focus.y * image.height - cropHeight / 2, 0), image.height - cropHeight)
+        : (image.height - cropHeight) * (align?.y ?? 0.5);
     }
 
     return (
@@ -82,9 +284,15 @@ function CanvasImage({
       renderWidth = height * imageRatio;
     }
 
+    // Shift the image inside the box; rotation stays around the box center
+    const offsetX = width / 2 - (width - renderWidth) * (align?.x ?? 0.5);
+    const offsetY = height / 2 - (height - renderHeight) * (align?.y ?? 0.5);
+
     return (
       <KonvaImage
         {...commonProps}
+        offsetX={offsetX}
+        offsetY={offsetY}
         image={image}
         width={renderWidth}
         height={renderHeight}
@@ -107,6 +315,7 @@ export function Canvas() {
   const {
     frame,
     objects,
+    tableData,
     selectedIds,
     zoom,
     selectObject,
@@ -247,37 +456,33 @@ export function Canvas() {
     };
 
     switch (obj.type) {
-      case 'text': {
-        const textObj = obj as TextObject;
-        return (
-          <Text
-            key={obj.id}
-            {...commonProps}
-            text={textObj.content}
-            fontSize={textObj.fontSize}
-            fontFamily={textObj.fontFamily}
-            fontStyle={`${textObj.fontWeight} ${textObj.fontStyle}`}
-            fill={textObj.fill}
-            align={textObj.align}
-            verticalAlign={textObj.verticalAlign}
-            width={textObj.width}
-            lineHeight={textObj.lineHeight}
-          />
-        );
-      }
+      case 'text':
+        return <TextPreview key={obj.id} text={obj as TextObject} commonProps={commonProps} />;
       case 'hero': {
         const heroObj = obj as HeroObject;
+        const focus = parseFocalPoint(tableData[heroObj.originalPath]?.[FOCAL_POINT_KEY]);
         return (
           <CanvasImage
             key={obj.id}
             src={heroObj.src}
-            commonProps={commonProps}
+            commonProps={{ ...commonProps, ...decorationProps(heroObj) }}
             width={obj.width}
             height={obj.height}
-            scaleMode="fit"
+            scaleMode={heroObj.scaleMode ?? 'fit'}
+            align={{
+              x: heroObj.align === 'left' ? 0 : heroObj.align === 'right' ? 1 : 0.5,
+              y: heroObj.verticalAlign === 'top' ? 0 : heroObj.verticalAlign === 'bottom' ? 1 : 0.5,
+            }}
+            focus={focus && { x: focus.x / 100, y: focus.y / 100 }}
           />
         );
       }
+      case 'shape':
+        return (
+          <Group key={obj.id} {...commonProps}>
+            <ShapePreview shape={obj as ShapeObject} />
+          </Group>
+        );
       case 'image':
       case 'background': {
         const imgObj = obj as ImageObject | BackgroundObject;
@@ -285,7 +490,7 @@ export function Canvas() {
           <CanvasImage
             key={obj.id}
             src={imgObj.src}
-            commonProps={commonProps}
+            commonProps={obj.type === 'image' ? { ...commonProps, ...decorationProps(imgObj as ImageObject) } : commonProps}
             width={obj.width}
             height={obj.height}
             scaleMode={'scaleMode' in imgObj ? imgObj.scaleMode : 'fit'}
@@ -297,6 +502,47 @@ export function Canvas() {
     }
   };
 
+  // Previews shape masks and clipping groups by clipping the layer to an outline. Image masks are applied
+  // at generation only. A hidden base hides its clipping group, as in generation.
+  const renderLayer = (obj: CanvasObject, index: number) => {
+    const base = clipBase(objects, index);
+    if (base && !base.visible) return null;
+    let node = renderObject(obj);
+    if (!node) return null;
+
+    const mask = obj.mask;
+    if (mask?.type === 'shape') {
+      const outline: Outline = {
+        shape: mask.shape === 'ellipse' ? 'ellipse' : 'rect',
+        x: 0,
+        y: 0,
+        width: obj.width,
+        height: obj.height,
+        cornerRadius: mask.cornerRadius ?? 0,
+      };
+      node = (
+        <Group
+          key={obj.id}
+          clipFunc={(ctx) => {
+            if (mask.invert) ctx.rect(0, 0, frame.width, frame.height);
+            traceOutline(ctx, obj, outline, mask.invert);
+          }}
+        >
+          {node}
+        </Group>
+      );
+    }
+    if (base) {
+      const outline = baseOutline(base);
+      node = (
+        <Group key={obj.id} clipFunc={(ctx) => traceOutline(ctx, base, outline)}>
+          {node}
+        </Group>
+      );
+    }
+    return node;
+  };
+
   const canvasWidth = frame.width * zoom;
   const canvasHeight = frame.height * zoom;
 
@@ -323,9 +569,20 @@ export function Canvas() {
             onClick={handleFrameClick}
             onTap={handleFrameClick}
           />
+          {frame.backgroundGradient && (
+            <Rect
+              x={0}
+              y={0}
+              width={frame.width}
+              height={frame.height}
+              {...gradientProps(frame.backgroundGradient, frame.width, frame.height)}
+              onClick={handleFrameClick}
+              onTap={handleFrameClick}
+            />
+          )}
 
           {/* Render all objects */}
-          {objects.map(renderObject)}
+          {objects.map(renderLayer)}
 
           {/* Selection transformer */}
           <Transformer
@@ -1,4 +1,5 @@
 import { useEditorStore, type ImageFile } from '../../store/editorStore';
+import { useThumbnail } from '../../hooks/useThumbnail';
 import styles from './ImageThumbnail.module.css';
 
 interface ImageThumbnailProps {
@@ -8,23 +9,29 @@ interface ImageThumbnailProps {
 
 export function ImageThumbnail({ image, isSelected }: ImageThumbnailProps) {
   const { toggleImageSelection } = useEditorStore();
+  const thumbnailSrc = useThumbnail(image);
 
   const handleClick = () => {
     toggleImageSelection(image.path);
   };
 
+  const details = image.width && image.height ? `${image.width}×${image.height}` : null;
+  const title = [image.relativePath || image.name, details].filter(Boolean).join('\n');
+
   return (
     <div
       className={`${styles.thumbnail} ${isSelected ? styles.selected : ''}`}
       onClick={handleClick}
     >
       <div className={styles.imageWrapper}>
-        <img
-          src={image.thumbnailUrl}
-          alt={image.name}
-          className={styles.image}
-          loading="lazy"
-        />
+        {thumbnailSrc && (
+          <img
+            src={thumbnailSrc}
+            alt={image.name}
+            className={styles.image}
+            loading="lazy"
+          />
+        )}
         <div className={styles.checkbox}>
           {isSelected ? (
             <svg
@@ -38,7 +45,7 @@ export function ImageThumbnail({ image, isSelected }: ImageThumbnailProps) {
           ) : null}
         </div>
       </div>
-      <div className={styles.name} title={image.name} onClick={(e) => e.stopPropagation()}>
+      <div className={styles.name} title={title} onClick={(e) => e.stopPropagation()}>
         {image.name}
       </div>
     </div>
@@ -51,6 +51,8 @@ function LayerItem({
         return 'B';
       case 'hero':
         return 'H';
+      case 'shape':
+        return 'S';
       default:
         return '?';
     }
@@ -1,10 +1,22 @@
 import { useEditorStore, FRAME_ID } from '../../store/editorStore';
 import { useSystemFonts } from '../../hooks/useSystemFonts';
-import type { TextObject, HeroObject, CanvasObject } from '../../types/canvas';
+import type {
+  TextObject,
+  HeroObject,
+  ImageObject,
+  BackgroundObject,
+  ShapeObject,
+  CanvasObject,
+  Adjustment,
+  AdjustmentType,
+  Gradient,
+  GradientStop,
+  LayerMask,
+} from '../../types/canvas';
 import { ASPECT_RATIO_PRESETS } from '../../types/canvas';
 import styles from './PropertiesPanel.module.css';
 
-function FrameProperties() {
+function FrameProperties({ fonts }: { fonts: string[] }) {
   const { frame, setFrame } = useEditorStore();
 
   const handleAspectRatioChange = (ratio: '1:1' | '4:3' | '16:9') => {
@@ -60,10 +72,163 @@ function FrameProperties() {
           className={styles.colorInput}
         />
       </div>
+      <GradientFields
+        label="Background Gradient"
+        gradient={frame.backgroundGradient}
+        onChange={(backgroundGradient) => setFrame({ backgroundGradient })}
+      />
+      <FallbackFontsFields fonts={fonts} />
     </div>
   );
 }
 
+// Families tried, in order, for characters missing from a text layer's font (before installed fonts)
+function FallbackFontsFields({ fonts }: { fonts: string[] }) {
+  const { frame, setFrame } = useEditorStore();
+  const fallbackFonts = frame.fallbackFonts ?? [];
+
+  return (
+    <>
+      <div className={styles.field}>
+        <label>Fallback Fonts</label>
+        <select
+          value=""
+          onChange={(e) => e.target.value && setFrame({ fallbackFonts: [...fallbackFonts, e.target.value] })}
+        >
+          <option value="">Add font…</option>
+          {fonts
+            .filter((font) => !fallbackFonts.includes(font))
+            .map((font) => (
+              <option key={font} value={font} style={{ fontFamily: font }}>
+                {font}
+              </option>
+            ))}
+        </select>
+      </div>
+      {fallbackFonts.map((font, index) => (
+        <div key={font} className={styles.adjustmentRow}>
+          <span className={styles.fileName}>{font}</span>
+          <button
+            className={styles.iconButton}
+            onClick={() => setFrame({ fallbackFonts: fallbackFonts.filter((_, i) => i !== index) })}
+            title="Remove"
+          >
+            ×
+          </button>
+        </div>
+      ))}
+    </>
+  );
+}
+
+const DEFAULT_GRADIENT: Gradient = {
+  type: 'linear',
+  angle: 180,
+  stops: [
+    { color: '#ffffff', position: 0 },
+    { color: '#000000', position: 1 },
+  ],
+};
+
+// Gradient fill editor (frame background, shape and text fill); gradients are dithered during generation
+function GradientFields({
+  label,
+  gradient,
+  onChange: setGradient,
+}: {
+  label: string;
+  gradient: Gradient | undefined;
+  onChange: (gradient: Gradient | undefined) => void;
+}) {
+  const updateStop = (gradient: Gradient, index: number, changes: Partial<GradientStop>) =>
+    setGradient({ ...gradient, stops: gradient.stops.map((s, i) => (i === index ? { ...s, ...changes } : s)) });
+
+  return (
+    <>
+      <div className={styles.field}>
+        <label>{label}</label>
+        <select
+          value={gradient?.type ?? 'none'}
+          onChange={(e) => {
+            const type = e.target.value;
+            setGradient(type === 'none' ? undefined : { ...(gradient ?? DEFAULT_GRADIENT), type: type as Gradient['type'] });
+          }}
+        >
+          <option value="none">None</option>
+          <option value="linear">Linear</option>
+          <option value="radial">Radial</option>
+        </select>
+      </div>
+      {gradient?.type === 'linear' && (
+        <div className={styles.field}>
+          <label>Angle, °</label>
+          <input
+            type="number"
+            step={15}
+            value={gradient.angle ?? 180}
+            onChange={(e) => setGradient({ ...gradient, angle: Number(e.target.value) })}
+          />
+        </div>
+      )}
+      {gradient?.type === 'radial' && (
+        <div className={styles.row}>
+          <div className={styles.field}>
+            <label>Center X, %</label>
+            <input
+              type="number"
+              value={gradient.centerX ?? 50}
+              onChange={(e) => setGradient({ ...gradient, centerX: Number(e.target.value) })}
+            />
+          </div>
+          <div className={styles.field}>
+            <label>Center Y, %</label>
+            <input
+              type="number"
+              value={gradient.centerY ?? 50}
+              onChange={(e) => setGradient({ ...gradient, centerY: Number(e.target.value) })}
+            />
+          </div>
+        </div>
+      )}
+      {gradient && (
+        <>
+          {gradient.stops.map((stop, index) => (
+            <div key={index} className={styles.adjustmentRow}>
+              <input
+                type="color"
+                value={stop.color.slice(0, 7)}
+                onChange={(e) => updateStop(gradient, index, { color: e.target.value })}
+              />
+              <input
+                type="number"
+                min={0}
+                max={100}
+                value={Math.round(stop.position * 100)}
+                onChange={(e) => updateStop(gradient, index, { position: Number(e.target.value) / 100 })}
+                title="Position, %"
+              />
+              <button
+                className={styles.iconButton}
+                onClick={() => setGradient({ ...gradient, stops: gradient.stops.filter((_, i) => i !== index) })}
+                disabled={gradient.stops.length <= 2}
+                title="Remove stop"
+              >
+                ×
+              </button>
+            </div>
+          ))}
+          <button
+            className={styles.addButton}
+            onClick={() => setGradient({ ...gradient, stops: [...gradient.stops, { color: '#808080', position: 0.5 }] })}
+          >
+            + Add color stop
+          </button>
+        </>
+      )}
+    </>
+  );
+}
+
 function TransformProperties({ object }: { object: CanvasObject }) {
   const { updateObject } = useEditorStore();
 
@@ -206,7 +371,7 @@ function AlignmentProperties() {
 }
 
 function HeroProperties({ object }: { object: HeroObject }) {
-  const { openGalleryForReplacement } = useEditorStore();
+  const { openGalleryForReplacement, updateObject } = useEditorStore();
 
   // Extract filename from path
   const fileName = object.originalPath.split('/').pop() || 'Unknown';
@@ -220,6 +385,75 @@ function HeroProperties({ object }: { object: HeroObject }) {
           {fileName}
         </div>
       </div>
+      <div className={styles.field}>
+        <label>Масштаб</label>
+        <select
+          value={object.scaleMode ?? 'fit'}
+          onChange={(e) => updateObject(object.id, { scaleMode: e.target.value as 'fill' | 'fit' | 'stretch' })}
+        >
+          <option value="fit">Вписать</option>
+          <option value="fill">Заполнить (с обрезкой)</option>
+          <option value="stretch">Растянуть</option>
+        </select>
+      </div>
+      {object.scaleMode !== 'stretch' && (
+        <div className={styles.row}>
+          <div className={styles.field}>
+            <label>По горизонтали</label>
+            <select
+              value={object.align ?? 'center'}
+              onChange={(e) => updateObject(object.id, { align: e.target.value as 'left' | 'center' | 'right' })}
+            >
+              <option value="left">Слева</option>
+              <option value="center">По центру</option>
+              <option value="right">Справа</option>
+            </select>
+          </div>
+          <div className={styles.field}>
+            <label>По вертикали</label>
+            <select
+              value={object.verticalAlign ?? 'middle'}
+              onChange={(e) =>
+                updateObject(object.id, { verticalAlign: e.target.value as 'top' | 'middle' | 'bottom' })
+              }
+            >
+              <option value="top">Сверху</option>
+              <option value="middle">По центру</option>
+              <option value="bottom">Снизу</option>
+            </select>
+          </div>
+        </div>
+      )}
+      {object.scaleMode === 'fill' && (
+        <div className={styles.note}>Точку фокуса для обрезки можно задать на вкладке «Таблица»</div>
+      )}
+      <KeyingFields object={object} />
+      <AdjustmentsFields object={object} />
+      <DecorationFields object={object} />
+      <label className={styles.checkboxField}>
+        <input
+          type="checkbox"
+          checked={object.autoTrim ?? false}
+          onChange={(e) => updateObject(object.id, { autoTrim: e.target.checked })}
+        />
+        Обрезать поля
+      </label>
+      {object.autoTrim && (
+        <>
+          <div className={styles.field}>
+            <label>Отступ вокруг товара, %</label>
+            <input
+              type="number"
+              min={0}
+              max={50}
+              step={1}
+              value={object.trimPadding ?? 0}
+              onChange={(e) => updateObject(object.id, { trimPadding: Number(e.target.value) })}
+            />
+          </div>
+          <div className={styles.note}>Поля обрезаются при генерации, чтобы товары на всех карточках были одного размера</div>
+        </>
+      )}
       <button className={styles.replaceButton} onClick={openGalleryForReplacement}>
         Заменить изображение
       </button>
@@ -227,6 +461,451 @@ function HeroProperties({ object }: { object: HeroObject }) {
   );
 }
 
+function ImageProperties({ object }: { object: ImageObject }) {
+  return (
+    <div className={styles.section}>
+      <div className={styles.sectionTitle}>Изображение</div>
+      <KeyingFields object={object} />
+      <AdjustmentsFields object={object} />
+      <DecorationFields object={object} />
+    </div>
+  );
+}
+
+function BackgroundProperties({ object }: { object: BackgroundObject }) {
+  return (
+    <div className={styles.section}>
+      <div className={styles.sectionTitle}>Фон</div>
+      <AdjustmentsFields object={object} />
+    </div>
+  );
+}
+
+function ShapeProperties({ object }: { object: ShapeObject }) {
+  const { updateObject } = useEditorStore();
+  const isLine = object.shape === 'line';
+
+  return (
+    <div className={styles.section}>
+      <div className={styles.sectionTitle}>Фигура</div>
+      <div className={styles.field}>
+        <label>Тип</label>
+        <select
+          value={object.shape}
+          onChange={(e) => updateObject(object.id, { shape: e.target.value as ShapeObject['shape'] })}
+        >
+          <option value="rect">Прямоугольник</option>
+          <option value="ellipse">Эллипс</option>
+          <option value="line">Линия</option>
+        </select>
+      </div>
+      {!isLine && (
+        <>
+          <label className={styles.checkboxField}>
+            <input
+              type="checkbox"
+              checked={object.fill !== undefined}
+              onChange={(e) => updateObject(object.id, { fill: e.target.checked ? '#d9d9d9' : undefined })}
+            />
+            Заливка
+          </label>
+          {object.fill !== undefined && !object.fillGradient && (
+            <div className={styles.field}>
+              <label>Цвет заливки</label>
+              <input
+                type="color"
+                value={object.fill}
+                onChange={(e) => updateObject(object.id, { fill: e.target.value })}
+                className={styles.colorInput}
+              />
+            </div>
+          )}
+          <GradientFields
+            label="Градиент"
+            gradient={object.fillGradient}
+            onChange={(fillGradient) => updateObject(object.id, { fillGradient })}
+          />
+        </>
+      )}
+      <div className={styles.row}>
+        <div className={styles.field}>
+          <label>{isLine ? 'Толщина, px' : 'Обводка, px'}</label>
+          <input
+            type="number"
+            min={0}
+            value={object.strokeWidth ?? 0}
+            onChange={(e) =>
+              updateObject(object.id, { strokeWidth: Number(e.target.value), stroke: object.stroke ?? '#000000' })
+            }
+          />
+        </div>
+        <div className={styles.field}>
+          <label>{isLine ? 'Цвет' : 'Цвет обводки'}</label>
+          <input
+            type="color"
+            value={object.stroke ?? '#000000'}
+            onChange={(e) => updateObject(object.id, { stroke: e.target.value })}
+            className={styles.colorInput}
+          />
+        </div>
+      </div>
+      {object.shape === 'rect' && (
+        <div className={styles.field}>
+          <label>Скругление, px</label>
+          <input
+            type="number"
+            min={0}
+            value={object.cornerRadius ?? 0}
+            onChange={(e) => updateObject(object.id, { cornerRadius: Number(e.target.value) })}
+          />
+        </div>
+      )}
+    </div>
+  );
+}
+
+// Layer mask and clipping to the layer below (applied during generation; the editor shows the layer unmasked)
+function MaskProperties({ object }: { object: CanvasObject }) {
+  const { updateObject, allImages } = useEditorStore();
+  const mask = object.mask;
+
+  const setMask = (next: LayerMask | undefined) => updateObject(object.id, { mask: next });
+
+  return (
+    <div className={styles.section}>
+      <div className={styles.sectionTitle}>Маска</div>
+      <div className={styles.field}>
+        <label>Тип маски</label>
+        <select
+          value={mask?.type ?? 'none'}
+          onChange={(e) => {
+            const type = e.target.value;
+            setMask(type === 'none' ? undefined : { ...mask, type: type as LayerMask['type'] });
+          }}
+        >
+          <option value="none">Нет</option>
+          <option value="shape">Фигура</option>
+          <option value="alpha">Прозрачность изображения</option>
+          <option value="luminance">Яркость изображения</option>
+        </select>
+      </div>
+      {mask?.type === 'shape' && (
+        <div className={styles.row}>
+          <div className={styles.field}>
+            <label>Фигура</label>
+            <select
+              value={mask.shape ?? 'rect'}
+              onChange={(e) => setMask({ ...mask, shape: e.target.value as LayerMask['shape'] })}
+            >
+              <option value="rect">Прямоугольник</option>
+              <option value="ellipse">Эллипс</option>
+            </select>
+          </div>
+          {(mask.shape ?? 'rect') === 'rect' && (
+            <div className={styles.field}>
+              <label>Скругление, px</label>
+              <input
+                type="number"
+                min={0}
+                value={mask.cornerRadius ?? 0}
+                onChange={(e) => setMask({ ...mask, cornerRadius: Number(e.target.value) })}
+              />
+            </div>
+          )}
+        </div>
+      )}
+      {mask && mask.type !== 'shape' && (
+        <div className={styles.field}>
+          <label>Изображение маски</label>
+          <select value={mask.src ?? ''} onChange={(e) => setMask({ ...mask, src: e.target.value || undefined })}>
+            <option value="">Не выбрано</option>
+            {allImages.map((image) => (
+              <option key={image.path} value={image.path}>
+                {image.relativePath}
+              </option>
+            ))}
+          </select>
+        </div>
+      )}
+      {mask && (
+        <label className={styles.checkboxField}>
+          <input
+            type="checkbox"
+            checked={mask.invert ?? false}
+            onChange={(e) => setMask({ ...mask, invert: e.target.checked })}
+          />
+          Инвертировать
+        </label>
+      )}
+      <label className={styles.checkboxField}>
+        <input
+          type="checkbox"
+          checked={object.clipToBelow ?? false}
+          onChange={(e) => updateObject(object.id, { clipToBelow: e.target.checked })}
+        />
+        Обрезать по слою ниже
+      </label>
+      {(mask || object.clipToBelow) && (
+        <div className={styles.note}>Маска и обрезка применяются при генерации; в редакторе слой показан целиком</div>
+      )}
+    </div>
+  );
+}
+
+// Corner radius, inside border and drop shadow (the shadow follows the image's transparency)
+function DecorationFields({ object }: { object: HeroObject | ImageObject }) {
+  const { updateObject } = useEditorStore();
+
+  return (
+    <>
+      <div className={styles.subsectionTitle}>Оформление</div>
+      <div className={styles.row}>
+        <div className={styles.field}>
+          <label>Скругление, px</label>
+          <input
+            type="number"
+            min={0}
+            value={object.cornerRadius ?? 0}
+            onChange={(e) => updateObject(object.id, { cornerRadius: Number(e.target.value) })}
+          />
+        </div>
+        <div className={styles.field}>
+          <label>Обводка, px</label>
+          <input
+            type="number"
+            min={0}
+            value={object.strokeWidth ?? 0}
+            onChange={(e) =>
+              updateObject(object.id, { strokeWidth: Number(e.target.value), stroke: object.stroke ?? '#ffffff' })
+            }
+          />
+        </div>
+      </div>
+      {(object.strokeWidth ?? 0) > 0 && (
+        <div className={styles.field}>
+          <label>Цвет обводки</label>
+          <input
+            type="color"
+            value={object.stroke ?? '#ffffff'}
+            onChange={(e) => updateObject(object.id, { stroke: e.target.value })}
+          />
+        </div>
+      )}
+      <ShadowFields object={object} />
+    </>
+  );
+}
+
+// Drop shadow (cast by the layer's opaque pixels, or by the text background box)
+function ShadowFields({ object }: { object: HeroObject | ImageObject | TextObject }) {
+  const { updateObject } = useEditorStore();
+
+  return (
+    <>
+      <label className={styles.checkboxField}>
+        <input
+          type="checkbox"
+          checked={!!object.shadowColor}
+          onChange={(e) =>
+            updateObject(object.id, {
+              shadowColor: e.target.checked ? '#000000' : undefined,
+              shadowBlur: object.shadowBlur ?? 16,
+              shadowOffsetY: object.shadowOffsetY ?? 8,
+              shadowOpacity: object.shadowOpacity ?? 0.4,
+            })
+          }
+        />
+        Тень
+      </label>
+      {object.shadowColor && (
+        <>
+          <div className={styles.row}>
+            <div className={styles.field}>
+              <label>Цвет</label>
+              <input
+                type="color"
+                value={object.shadowColor}
+                onChange={(e) => updateObject(object.id, { shadowColor: e.target.value })}
+              />
+            </div>
+            <div className={styles.field}>
+              <label>Непрозрачность</label>
+              <input
+                type="number"
+                min={0}
+                max={1}
+                step={0.1}
+                value={object.shadowOpacity ?? 1}
+                onChange={(e) => updateObject(object.id, { shadowOpacity: Number(e.target.value) })}
+              />
+            </div>
+          </div>
+          <div className={styles.row}>
+            <div className={styles.field}>
+              <label>Смещение X</label>
+              <input
+                type="number"
+                value={object.shadowOffsetX ?? 0}
+                onChange={(e) => updateObject(object.id, { shadowOffsetX: Number(e.target.value) })}
+              />
+            </div>
+            <div className={styles.field}>
+              <label>Смещение Y</label>
+              <input
+                type="number"
+                value={object.shadowOffsetY ?? 0}
+                onChange={(e) => updateObject(object.id, { shadowOffsetY: Number(e.target.value) })}
+              />
+            </div>
+          </div>
+          <div className={styles.field}>
+            <label>Размытие, px</label>
+            <input
+              type="number"
+              min={0}
+              value={object.shadowBlur ?? 0}
+              onChange={(e) => updateObject(object.id, { shadowBlur: Number(e.target.value) })}
+            />
+          </div>
+        </>
+      )}
+    </>
+  );
+}
+
+const ADJUSTMENT_LABELS: Record<AdjustmentType, string> = {
+  brightness: 'Яркость, %',
+  contrast: 'Контраст, %',
+  saturation: 'Насыщенность, %',
+  hue: 'Оттенок, °',
+  grayscale: 'Ч/б, %',
+  blur: 'Размытие, px',
+};
+
+const ADJUSTMENT_DEFAULTS: Record<AdjustmentType, number> = {
+  brightness: -20,
+  contrast: 20,
+  saturation: -50,
+  hue: 30,
+  grayscale: 100,
+  blur: 8,
+};
+
+// Adjustments are applied during generation, in list order; the editor shows the original image
+function AdjustmentsFields({ object }: { object: HeroObject | ImageObject | BackgroundObject }) {
+  const { updateObject } = useEditorStore();
+  const adjustments = object.adjustments ?? [];
+
+  const setAdjustments = (next: Adjustment[]) => updateObject(object.id, { adjustments: next });
+  const update = (index: number, changes: Partial<Adjustment>) =>
+    setAdjustments(adjustments.map((a, i) => (i === index ? { ...a, ...changes } : a)));
+  const moveUp = (index: number) => {
+    const next = [...adjustments];
+    [next[index - 1], next[index]] = [next[index], next[index - 1]];
+    setAdjustments(next);
+  };
+
+  return (
+    <>
+      <div className={styles.subsectionTitle}>Коррекция</div>
+      {adjustments.map((adjustment, index) => (
+        <div key={index} className={styles.adjustmentRow}>
+          <select
+            value={adjustment.type}
+            onChange={(e) => {
+              const type = e.target.value as AdjustmentType;
+              update(index, { type, value: ADJUSTMENT_DEFAULTS[type] });
+            }}
+          >
+            {(Object.keys(ADJUSTMENT_LABELS) as AdjustmentType[]).map((type) => (
+              <option key={type} value={type}>
+                {ADJUSTMENT_LABELS[type]}
+              </option>
+            ))}
+          </select>
+          <input
+            type="number"
+            step={1}
+            value={adjustment.value}
+            onChange={(e) => update(index, { value: Number(e.target.value) })}
+          />
+          <button
+            className={styles.iconButton}
+            onClick={() => moveUp(index)}
+            disabled={index === 0}
+            title="Выше (применяется раньше)"
+          >
+            ↑
+          </button>
+          <button
+            className={styles.iconButton}
+            onClick={() => setAdjustments(adjustments.filter((_, i) => i !== index))}
+            title="Удалить"
+          >
+            ×
+          </button>
+        </div>
+      ))}
+      <button
+        className={styles.addButton}
+        onClick={() => setAdjustments([...adjustments, { type: 'brightness', value: ADJUSTMENT_DEFAULTS.brightness }])}
+      >
+        + Добавить коррекцию
+      </button>
+      {adjustments.length > 0 && (
+        <div className={styles.note}>Коррекция применяется при генерации сверху вниз; в редакторе показан оригинал</div>
+      )}
+    </>
+  );
+}
+
+// Background keying is applied during generation only; the editor shows the original image
+function KeyingFields({ object }: { object: HeroObject | ImageObject }) {
+  const { updateObject } = useEditorStore();
+
+  return (
+    <>
+      <label className={styles.checkboxField}>
+        <input
+          type="checkbox"
+          checked={object.keyBackground ?? false}
+          onChange={(e) => updateObject(object.id, { keyBackground: e.target.checked })}
+        />
+        Удалить однотонный фон
+      </label>
+      {object.keyBackground && (
+        <>
+          <div className={styles.row}>
+            <div className={styles.field}>
+              <label>Допуск, %</label>
+              <input
+                type="number"
+                min={0}
+                max={100}
+                step={1}
+                value={object.keyTolerance ?? 8}
+                onChange={(e) => updateObject(object.id, { keyTolerance: Number(e.target.value) })}
+              />
+            </div>
+            <div className={styles.field}>
+              <label>Растушёвка, px</label>
+              <input
+                type="number"
+                min={0}
+                max={20}
+                step={0.5}
+                value={object.keyFeather ?? 1}
+                onChange={(e) => updateObject(object.id, { keyFeather: Number(e.target.value) })}
+              />
+            </div>
+          </div>
+          <div className={styles.note}>Фон убирается при генерации; в редакторе показан оригинал</div>
+        </>
+      )}
+    </>
+  );
+}
+
 function TextProperties({ object, fonts }: { object: TextObject; fonts: string[] }) {
   const { updateObject } = useEditorStore();
 
@@ -270,6 +949,11 @@ function TextProperties({ object, fonts }: { object: TextObject; fonts: string[]
           />
         </div>
       </div>
+      <GradientFields
+        label="Gradient"
+        gradient={object.fillGradient}
+        onChange={(fillGradient) => updateObject(object.id, { fillGradient })}
+      />
       <div className={styles.field}>
         <label>Font Family</label>
         <select
@@ -306,10 +990,100 @@ function TextProperties({ object, fonts }: { object: TextObject; fonts: string[]
           </select>
         </div>
       </div>
+      <label className={styles.checkboxField} title="**bold**, ~~strike~~, __underline__, [color=#E53935]…[/color], [size=48]…[/size]">
+        <input
+          type="checkbox"
+          checked={object.markup ?? false}
+          onChange={(e) => updateObject(object.id, { markup: e.target.checked })}
+        />
+        Markup in values
+      </label>
+      <TextEffectsFields object={object} />
     </div>
   );
 }
 
+// Outline (grows outwards from the letters), drop shadow and background box
+function TextEffectsFields({ object }: { object: TextObject }) {
+  const { updateObject } = useEditorStore();
+
+  return (
+    <>
+      <div className={styles.subsectionTitle}>Эффекты</div>
+      <div className={styles.row}>
+        <div className={styles.field}>
+          <label>Обводка, px</label>
+          <input
+            type="number"
+            min={0}
+            value={object.strokeWidth ?? 0}
+            onChange={(e) =>
+              updateObject(object.id, { strokeWidth: Number(e.target.value), stroke: object.stroke ?? '#ffffff' })
+            }
+          />
+        </div>
+        {(object.strokeWidth ?? 0) > 0 && (
+          <div className={styles.field}>
+            <label>Цвет обводки</label>
+            <input
+              type="color"
+              value={object.stroke ?? '#ffffff'}
+              onChange={(e) => updateObject(object.id, { stroke: e.target.value })}
+            />
+          </div>
+        )}
+      </div>
+      <label className={styles.checkboxField}>
+        <input
+          type="checkbox"
+          checked={!!object.backgroundColor}
+          onChange={(e) =>
+            updateObject(object.id, {
+              backgroundColor: e.target.checked ? '#ffeb3b' : undefined,
+              backgroundPadding: object.backgroundPadding ?? 8,
+            })
+          }
+        />
+        Подложка
+      </label>
+      {object.backgroundColor && (
+        <div className={styles.row}>
+          <div className={styles.field}>
+            <label>Цвет</label>
+            <input
+              type="color"
+              value={object.backgroundColor}
+              onChange={(e) => updateObject(object.id, { backgroundColor: e.target.value })}
+            />
+          </div>
+          <div className={styles.field}>
+            <label>Отступ, px</label>
+            <input
+              type="number"
+              min={0}
+              value={object.backgroundPadding ?? 0}
+              onChange={(e) => updateObject(object.id, { backgroundPadding: Number(e.target.value) })}
+            />
+          </div>
+          <div className={styles.field}>
+            <label>Скругление, px</label>
+            <input
+              type="number"
+              min={0}
+              value={object.cornerRadius ?? ''}
+              placeholder="Авто"
+              onChange={(e) =>
+                updateObject(object.id, { cornerRadius: e.target.value === '' ? undefined : Number(e.target.value) })
+              }
+            />
+          </div>
+        </div>
+      )}
+      <ShadowFields object={object} />
+    </>
+  );
+}
+
 export function PropertiesPanel() {
   const { objects, selectedIds } = useEditorStore();
   const { fonts } = useSystemFonts();
@@ -324,7 +1098,7 @@ export function PropertiesPanel() {
       <div className={styles.header}>Properties</div>
       <div className={styles.content}>
         {/* Frame is selected - show only Frame properties */}
-        {isFrameSelected && <FrameProperties />}
+        {isFrameSelected && <FrameProperties fonts={fonts} />}
 
         {/* Object is selected - show Alignment first, then other properties */}
         {selectedObject && (
@@ -334,9 +1108,19 @@ export function PropertiesPanel() {
             {selectedObject.type === 'hero' && (
               <HeroProperties object={selectedObject as HeroObject} />
             )}
+            {selectedObject.type === 'background' && (
+              <BackgroundProperties object={selectedObject as BackgroundObject} />
+            )}
+            {selectedObject.type === 'image' && (
+              <ImageProperties object={selectedObject as ImageObject} />
+            )}
+            {selectedObject.type === 'shape' && (
+              <ShapeProperties object={selectedObject as ShapeObject} />
+            )}
             {selectedObject.type === 'text' && (
               <TextProperties object={selectedObject as TextObject} fonts={fonts} />
             )}
+            <MaskProperties object={selectedObject} />
           </>
         )}
 
@@ -1,18 +1,14 @@
 import { useState } from 'react';
 import { open } from '@tauri-apps/plugin-dialog';
 import { invoke } from '@tauri-apps/api/core';
-import { convertFileSrc } from '@tauri-apps/api/core';
-import { useEditorStore, type ImageFile } from '../../store/editorStore';
+import { useEditorStore, toImageFile, type ImageFileInfo } from '../../store/editorStore';
+import { THUMBNAIL_SIZE } from '../../hooks/useThumbnail';
 import styles from './StartupWindow.module.css';
 
-interface ImageFileInfo {
-  path: string;
-  name: string;
-}
-
 export function StartupWindow() {
   const [loading, setLoading] = useState(false);
   const [error, setError] = useState<string | null>(null);
+  const [includeSubfolders, setIncludeSubfolders] = useState(false);
 
   const { setWorkingDirectory, setAllImages, setAppPhase } = useEditorStore();
 
@@ -35,6 +31,7 @@ export function StartupWindow() {
       // Get images from directory via Tauri command
       const images = await invoke<ImageFileInfo[]>('list_images_in_directory', {
         directory,
+        options: { recursive: includeSubfolders },
       });
 
       if (images.length === 0) {
@@ -44,11 +41,19 @@ export function StartupWindow() {
       }
 
       // Convert to ImageFile format with asset URLs
-      const imageFiles: ImageFile[] = images.map((img) => ({
-        path: img.path,
-        name: img.name,
-        thumbnailUrl: convertFileSrc(img.path),
-      }));
+      const imageFiles = images.map(toImageFile);
+
+      // Keep the list in sync with files added/removed/changed later (relative to this scan)
+      invoke('watch_images', { directory, options: { recursive: includeSubfolders }, images }).catch((err) =>
+        console.warn('Failed to watch directory:', err)
+      );
+
+      // Generate gallery thumbnails in the background
+      invoke('prefetch_thumbnails', {
+        workingDirectory: directory,
+        paths: images.map((img) => img.path),
+        size: THUMBNAIL_SIZE,
+      }).catch((err) => console.warn('Failed to prefetch thumbnails:', err));
 
       // Extract folder name from path
       const folderName = directory.split('/').pop() || directory;
@@ -115,6 +120,16 @@ export function StartupWindow() {
           )}
         </button>
 
+        <label className={styles.option}>
+          <input
+            type="checkbox"
+            checked={includeSubfolders}
+            onChange={(e) => setIncludeSubfolders(e.target.checked)}
+            disabled={loading}
+          />
+          Include subfolders
+        </label>
+
         <p className={styles.hint}>
           Supported formats: JPG, PNG, GIF, WebP, BMP, SVG
         </p>
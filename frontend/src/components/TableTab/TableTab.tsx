@@ -1,21 +1,81 @@
-import { useState, useEffect } from 'react';
+import { useState, useEffect, useCallback } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
-import { useEditorStore } from '../../store/editorStore';
-import type { TextObject, ImageObject } from '../../types/canvas';
+import { useEditorStore, toImageFileInfo, FOCAL_POINT_KEY, parseFocalPoint } from '../../store/editorStore';
+import type { TextObject, ImageObject, HeroObject } from '../../types/canvas';
 import { BulkFillDialog } from './BulkFillDialog';
 import styles from './TableTab.module.css';
 
+interface GenerationError {
+  path: string; // Source image
+  name: string;
+  stage: 'decode' | 'render' | 'encode' | 'write';
+  kind: string; // e.g. 'not_found', 'corrupt_data', 'disk_full'
+  message: string;
+}
+
 interface GenerateResult {
   success: boolean;
+  job_id: string;
   generated_files: string[];
-  errors: string[];
+  errors: GenerationError[];
+  warnings: string[];
 }
 
+const STAGE_LABELS: Record<GenerationError['stage'], string> = {
+  decode: 'чтение',
+  render: 'отрисовка',
+  encode: 'кодирование',
+  write: 'запись',
+};
+
 interface GenerationProgress {
-  current: number;
+  current: number; // Finished images, including failed
   total: number;
   current_file: string;
+  elapsed_ms: number;
+  eta_ms: number | null; // Unknown until the first image finishes
+  images_per_second: number;
+  bytes_written: number;
+  failed: number;
+  workers: (string | null)[]; // File each worker is processing (null = idle)
+}
+
+// Helper: "1 ч 05 мин", "3 мин 20 с", "15 с"
+function formatDuration(ms: number): string {
+  const seconds = Math.round(ms / 1000);
+  const h = Math.floor(seconds / 3600);
+  const m = Math.floor((seconds % 3600) / 60);
+  const s = seconds % 60;
+  if (h > 0) return `${h} ч ${String(m).padStart(2, '0')} мин`;
+  if (m > 0) return `${m} мин ${s} с`;
+  return `${s} с`;
+}
+
+// Helper: "850 КБ", "12.4 МБ", "1.20 ГБ"
+function formatBytes(bytes: number): string {
+  if (bytes >= 1024 ** 3) return `${(bytes / 1024 ** 3).toFixed(2)} ГБ`;
+  if (bytes >= 1024 ** 2) return `${(bytes / 1024 ** 2).toFixed(1)} МБ`;
+  return `${Math.round(bytes / 1024)} КБ`;
+}
+
+// Helper: Clicked point in percent of the image ("x,y", one decimal)
+function focalPointFromClick(e: React.MouseEvent<HTMLImageElement>): string {
+  const rect = e.currentTarget.getBoundingClientRect();
+  const x = ((e.clientX - rect.left) / rect.width) * 100;
+  const y = ((e.clientY - rect.top) / rect.height) * 100;
+  const clamp = (v: number) => Math.min(100, Math.max(0, v)).toFixed(1);
+  return `${clamp(x)},${clamp(y)}`;
+}
+
+interface JobSummary {
+  id: string;
+  created_at: number; // Unix time, ms
+  template_name: string | null;
+  total: number;
+  done: number;
+  failed: number;
+  pending: number;
 }
 
 interface CpuInfo {
@@ -35,6 +95,8 @@ export function TableTab() {
     workingDirectory,
     frame,
     currentTemplateName,
+    staleImages,
+    clearStaleImages,
   } = useEditorStore();
 
   const [bulkFillKey, setBulkFillKey] = useState<string | null>(null);
@@ -43,6 +105,9 @@ export function TableTab() {
   const [progress, setProgress] = useState<GenerationProgress | null>(null);
   const [cpuInfo, setCpuInfo] = useState<CpuInfo | null>(null);
   const [parallelism, setParallelism] = useState<number | null>(null);
+  const [applyExifOrientation, setApplyExifOrientation] = useState(true);
+  const [unfinishedJobs, setUnfinishedJobs] = useState<JobSummary[]>([]);
+  const [commandError, setCommandError] = useState<string | null>(null);
 
   // Load CPU info on mount
   useEffect(() => {
@@ -52,6 +117,18 @@ export function TableTab() {
     });
   }, []);
 
+  // Jobs interrupted by a crash or shutdown can be resumed
+  const refreshUnfinishedJobs = useCallback(() => {
+    if (!workingDirectory) return;
+    invoke<JobSummary[]>('list_unfinished_jobs', { workingDirectory })
+      .then(setUnfinishedJobs)
+      .catch((err) => console.warn('Failed to list unfinished jobs:', err));
+  }, [workingDirectory]);
+
+  useEffect(() => {
+    refreshUnfinishedJobs();
+  }, [refreshUnfinishedJobs]);
+
   // Listen for progress events
   useEffect(() => {
     const unlisten = listen<GenerationProgress>('generation-progress', (event) => {
@@ -69,51 +146,92 @@ export function TableTab() {
   const textObjects = objects.filter((o) => o.type === 'text' && o.visible) as TextObject[];
   const imageObjects = objects.filter((o) => o.type === 'image' && o.visible) as ImageObject[];
 
+  // Focal points only matter when the hero is cropped to fill its box
+  const hero = objects.find((o) => o.type === 'hero') as HeroObject | undefined;
+  const canPickFocus = hero?.scaleMode === 'fill';
+
   // Initialize table data when component mounts or objects change
   useEffect(() => {
     initializeTableData();
   }, [objects, selectedImagePaths, initializeTableData]);
 
-  const handleGenerate = async () => {
-    if (!workingDirectory || selectedImages.length === 0) return;
-
+  // Run a generation command, showing progress and the result
+  const runGeneration = async (command: string, args: Record<string, unknown>, total: number) => {
     setIsGenerating(true);
     setGenerateResult(null);
-    setProgress({ current: 0, total: selectedImages.length, current_file: '' });
+    setCommandError(null);
+    setProgress({
+      current: 0,
+      total,
+      current_file: '',
+      elapsed_ms: 0,
+      eta_ms: null,
+      images_per_second: 0,
+      bytes_written: 0,
+      failed: 0,
+      workers: [],
+    });
 
     try {
-      const result = await invoke<GenerateResult>('generate_infographics', {
+      const result = await invoke<GenerateResult>(command, args);
+      setGenerateResult(result);
+      return result;
+    } catch (error) {
+      setCommandError(String(error));
+      return null;
+    } finally {
+      setIsGenerating(false);
+      setProgress(null);
+      refreshUnfinishedJobs();
+    }
+  };
+
+  const handleGenerate = async () => {
+    if (!workingDirectory || selectedImages.length === 0) return;
+
+    const result = await runGeneration(
+      'generate_infographics',
+      {
         request: {
           workingDirectory,
           frame: {
             width: frame.width,
             height: frame.height,
             backgroundColor: frame.backgroundColor,
+            backgroundGradient: frame.backgroundGradient,
           },
           objects,
           tableData,
-          selectedImages: selectedImages.map((img) => ({
-            path: img.path,
-            name: img.name,
-          })),
+          selectedImages: selectedImages.map(toImageFileInfo),
           templateName: currentTemplateName,
           parallelism: parallelism,
+          applyExifOrientation,
+          fallbackFonts: frame.fallbackFonts,
         },
-      });
+      },
+      selectedImages.length
+    );
 
-      setGenerateResult(result);
-    } catch (error) {
-      setGenerateResult({
-        success: false,
-        generated_files: [],
-        errors: [String(error)],
-      });
-    } finally {
-      setIsGenerating(false);
-      setProgress(null);
+    // Images regenerated without errors are up to date again
+    if (result) {
+      const failed = new Set(result.errors.map((error) => error.path));
+      clearStaleImages(selectedImages.map((img) => img.path).filter((path) => !failed.has(path)));
     }
   };
 
+  const handleResumeJob = (job: JobSummary) => {
+    runGeneration('resume_job', { workingDirectory, jobId: job.id }, job.pending);
+  };
+
+  // Re-run only the images that failed, with the job's original settings
+  const handleRetryFailed = (jobId: string, total: number) => {
+    runGeneration('retry_failed', { workingDirectory, jobId }, total);
+  };
+
+  const handleDiscardJob = (job: JobSummary) => {
+    invoke('discard_job', { workingDirectory, jobId: job.id }).finally(refreshUnfinishedJobs);
+  };
+
   if (selectedImages.length === 0) {
     return (
       <div className={styles.tableTab}>
@@ -168,6 +286,15 @@ export function TableTab() {
               </select>
             </div>
           )}
+          <label className={styles.threadsLabel} title="Поворачивать фото по EXIF-тегу ориентации">
+            <input
+              type="checkbox"
+              checked={applyExifOrientation}
+              onChange={(e) => setApplyExifOrientation(e.target.checked)}
+              disabled={isGenerating}
+            />
+            EXIF-поворот
+          </label>
           <button
             className={styles.generateButton}
             onClick={handleGenerate}
@@ -189,17 +316,70 @@ export function TableTab() {
         </div>
       </div>
 
+      {!isGenerating && unfinishedJobs.filter((job) => job.id !== generateResult?.job_id).map((job) => (
+        <div key={job.id} className={`${styles.resultBanner} ${styles.pending}`}>
+          <span>
+            Незавершённая генерация от {new Date(job.created_at).toLocaleString()}
+            {job.template_name && <> ({job.template_name})</>}: готово {job.done} из {job.total}
+            {job.failed > 0 && <>, ошибок {job.failed}</>}
+          </span>
+          <div className={styles.bannerActions}>
+            {job.pending > 0 && (
+              <button className={styles.bannerButton} onClick={() => handleResumeJob(job)}>
+                Продолжить
+              </button>
+            )}
+            {job.failed > 0 && (
+              <button className={styles.bannerButton} onClick={() => handleRetryFailed(job.id, job.failed)}>
+                Повторить ошибки
+              </button>
+            )}
+            <button className={styles.closeBanner} onClick={() => handleDiscardJob(job)} title="Отменить">
+              ×
+            </button>
+          </div>
+        </div>
+      ))}
+
       {generateResult && (
         <div className={`${styles.resultBanner} ${generateResult.success ? styles.success : styles.error}`}>
-          {generateResult.success ? (
+          <div>
             <span>
               Создано {generateResult.generated_files.length} инфографик в папке infographics/
               {currentTemplateName && <>{currentTemplateName}/</>}
+              {generateResult.errors.length > 0 && <>, ошибок {generateResult.errors.length}:</>}
             </span>
-          ) : (
-            <span>Ошибка: {generateResult.errors.join(', ')}</span>
-          )}
-          <button className={styles.closeBanner} onClick={() => setGenerateResult(null)}>×</button>
+            {generateResult.errors.length > 0 && (
+              <ul className={styles.errorList}>
+                {generateResult.errors.map((error) => (
+                  <li key={error.path} title={error.path}>
+                    {error.name} ({STAGE_LABELS[error.stage]}): {error.message}
+                  </li>
+                ))}
+              </ul>
+            )}
+            {generateResult.warnings.length > 0 && (
+              <div>Предупреждения: {generateResult.warnings.join('; ')}</div>
+            )}
+          </div>
+          <div className={styles.bannerActions}>
+            {generateResult.errors.length > 0 && (
+              <button
+                className={styles.bannerButton}
+                onClick={() => handleRetryFailed(generateResult.job_id, generateResult.errors.length)}
+              >
+                Повторить
+              </button>
+            )}
+            <button className={styles.closeBanner} onClick={() => setGenerateResult(null)}>×</button>
+          </div>
+        </div>
+      )}
+
+      {commandError && (
+        <div className={`${styles.resultBanner} ${styles.error}`}>
+          <span>Ошибка: {commandError}</span>
+          <button className={styles.closeBanner} onClick={() => setCommandError(null)}>×</button>
         </div>
       )}
 
@@ -208,6 +388,7 @@ export function TableTab() {
           <thead>
             <tr>
               <th className={styles.headerCell}>Главное изображение</th>
+              {canPickFocus && <th className={styles.headerCell}>Фокус, %</th>}
               {imageObjects.map((img) => (
                 <th key={img.id} className={styles.headerCell}>
                   {img.name}
@@ -232,39 +413,76 @@ export function TableTab() {
             </tr>
           </thead>
           <tbody>
-            {selectedImages.map((image) => (
-              <tr key={image.path}>
-                <td className={styles.heroCell}>
-                  <img
-                    src={image.thumbnailUrl}
-                    alt={image.name}
-                    className={styles.heroImage}
-                  />
-                </td>
-                {imageObjects.map((img) => (
-                  <td key={img.id} className={styles.imageCell}>
-                    <img
-                      src={img.src}
-                      alt={img.name}
-                      className={styles.additionalImage}
-                    />
-                  </td>
-                ))}
-                {textObjects.map((text) => (
-                  <td key={text.id} className={styles.textCell}>
-                    <input
-                      type="text"
-                      className={styles.textInput}
-                      value={tableData[image.path]?.[text.key] || ''}
-                      onChange={(e) =>
-                        setTableTextValue(image.path, text.key, e.target.value)
-                      }
-                      placeholder={text.content}
-                    />
+            {selectedImages.map((image) => {
+              const focus = canPickFocus ? parseFocalPoint(tableData[image.path]?.[FOCAL_POINT_KEY]) : null;
+              return (
+                <tr key={image.path}>
+                  <td className={styles.heroCell}>
+                    <div className={styles.heroFrame}>
+                      <div className={styles.focusArea}>
+                        <img
+                          src={image.thumbnailUrl}
+                          alt={image.name}
+                          className={`${styles.heroImage} ${canPickFocus ? styles.focusPickable : ''}`}
+                          title={canPickFocus ? 'Клик — точка фокуса для обрезки, двойной клик — сбросить' : undefined}
+                          onClick={(e) => {
+                            if (canPickFocus) setTableTextValue(image.path, FOCAL_POINT_KEY, focalPointFromClick(e));
+                          }}
+                          onDoubleClick={() => {
+                            if (canPickFocus) setTableTextValue(image.path, FOCAL_POINT_KEY, '');
+                          }}
+                        />
+                        {focus && (
+                          <div className={styles.focusMarker} style={{ left: `${focus.x}%`, top: `${focus.y}%` }} />
+                        )}
+                      </div>
+                    </div>
+                    {staleImages[image.path] && (
+                      <div
+                        className={styles.staleBadge}
+                        title={['Файл изменён после генерации', ...staleImages[image.path]].join('\n')}
+                      >
+                        Изменён
+                      </div>
+                    )}
                   </td>
-                ))}
-              </tr>
-            ))}
+                  {canPickFocus && (
+                    <td className={styles.focusCell}>
+                      <input
+                        type="text"
+                        className={`${styles.textInput} ${tableData[image.path]?.[FOCAL_POINT_KEY] && !focus ? styles.textInputInvalid : ''}`}
+                        value={tableData[image.path]?.[FOCAL_POINT_KEY] || ''}
+                        onChange={(e) => setTableTextValue(image.path, FOCAL_POINT_KEY, e.target.value)}
+                        placeholder="по центру"
+                        title="Точка изображения, остающаяся в кадре при обрезке: «x,y» в процентах (или клик по миниатюре)"
+                      />
+                    </td>
+                  )}
+                  {imageObjects.map((img) => (
+                    <td key={img.id} className={styles.imageCell}>
+                      <img
+                        src={img.src}
+                        alt={img.name}
+                        className={styles.additionalImage}
+                      />
+                    </td>
+                  ))}
+                  {textObjects.map((text) => (
+                    <td key={text.id} className={styles.textCell}>
+                      <input
+                        type="text"
+                        className={styles.textInput}
+                        value={tableData[image.path]?.[text.key] || ''}
+                        onChange={(e) =>
+                          setTableTextValue(image.path, text.key, e.target.value)
+                        }
+                        placeholder={text.content}
+                      />
+                    </td>
+                  ))}
+                </tr>
+              );
+            })}
           </tbody>
         </table>
       </div>
@@ -283,13 +501,28 @@ export function TableTab() {
             <div className={styles.progressText}>
               Обработка {progress.current} из {progress.total}
             </div>
-            <div className={styles.progressFile}>{progress.current_file}</div>
             <div className={styles.progressBarContainer}>
               <div
                 className={styles.progressBar}
                 style={{ width: `${(progress.current / progress.total) * 100}%` }}
               />
             </div>
+            <div className={styles.progressStats}>
+              <span>Прошло: {formatDuration(progress.elapsed_ms)}</span>
+              <span>Осталось: {progress.eta_ms !== null ? formatDuration(progress.eta_ms) : '—'}</span>
+              <span>Скорость: {progress.images_per_second.toFixed(1)} изобр./с</span>
+              <span>Записано: {formatBytes(progress.bytes_written)}</span>
+              {progress.failed > 0 && (
+                <span className={styles.progressFailed}>Ошибок: {progress.failed}</span>
+              )}
+            </div>
+            <ul className={styles.progressWorkers}>
+              {progress.workers.map((file, i) => (
+                <li key={i} className={styles.progressFile}>
+                  Поток {i + 1}: {file ?? 'ожидание'}
+                </li>
+              ))}
+            </ul>
           </div>
         </div>
       )}
@@ -4,7 +4,7 @@ import { TemplateMenu } from './TemplateMenu';
 import styles from './Toolbar.module.css';
 
 export function Toolbar() {
-  const { addText, zoom, zoomIn, zoomOut, setZoom } = useEditorStore();
+  const { addText, addShape, zoom, zoomIn, zoomOut, setZoom } = useEditorStore();
 
   const handleAddText = () => {
     addText();
@@ -33,6 +33,18 @@ export function Toolbar() {
           <span className={styles.icon}>+</span>
           <span>Image</span>
         </button>
+        <button className={styles.button} onClick={() => addShape('rect')} title="Add Rectangle">
+          <span className={styles.icon}>▭</span>
+          <span>Rect</span>
+        </button>
+        <button className={styles.button} onClick={() => addShape('ellipse')} title="Add Ellipse">
+          <span className={styles.icon}>◯</span>
+          <span>Ellipse</span>
+        </button>
+        <button className={styles.button} onClick={() => addShape('line')} title="Add Line">
+          <span className={styles.icon}>―</span>
+          <span>Line</span>
+        </button>
       </div>
 
       <div className={styles.section}>
@@ -0,0 +1,17 @@
+import { useEffect } from 'react';
+import { listen } from '@tauri-apps/api/event';
+import { useEditorStore, type ImagesChanged } from '../store/editorStore';
+
+// Apply `images-changed` events from the backend directory watcher to the image list
+export function useImageWatcher() {
+  const applyImageChanges = useEditorStore((s) => s.applyImageChanges);
+
+  useEffect(() => {
+    const unlisten = listen<ImagesChanged>('images-changed', (event) => {
+      applyImageChanges(event.payload);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [applyImageChanges]);
+}
@@ -1,5 +1,6 @@
 import { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { useEditorStore } from '../store/editorStore';
 
 const DEFAULT_FONTS = [
   'Inter',
@@ -15,9 +16,11 @@ const DEFAULT_FONTS = [
 export function useSystemFonts() {
   const [fonts, setFonts] = useState<string[]>(DEFAULT_FONTS);
   const [loading, setLoading] = useState(true);
+  const workingDirectory = useEditorStore((s) => s.workingDirectory);
 
   useEffect(() => {
-    invoke<string[]>('get_system_fonts')
+    // Fonts from .infographics-fonts/ in the working directory are listed too
+    invoke<string[]>('get_system_fonts', { workingDirectory })
       .then((systemFonts) => {
         if (systemFonts && systemFonts.length > 0) {
           setFonts(systemFonts);
@@ -30,7 +33,7 @@ export function useSystemFonts() {
       .finally(() => {
         setLoading(false);
       });
-  }, []);
+  }, [workingDirectory]);
 
   return { fonts, loading };
 }
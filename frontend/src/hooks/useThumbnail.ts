@@ -0,0 +1,37 @@
+import { useState, useEffect } from 'react';
+import { invoke, convertFileSrc } from '@tauri-apps/api/core';
+import { useEditorStore, type ImageFile } from '../store/editorStore';
+
+// Longest side of gallery thumbnails, px (covers ~2x density for the grid cells)
+export const THUMBNAIL_SIZE = 320;
+
+// Cached thumbnail URL for an image; falls back to the original file if generation fails
+export function useThumbnail(image: ImageFile) {
+  const [src, setSrc] = useState<string | null>(null);
+  const workingDirectory = useEditorStore((s) => s.workingDirectory);
+
+  useEffect(() => {
+    let cancelled = false;
+    setSrc(null);
+
+    if (!workingDirectory) {
+      setSrc(image.thumbnailUrl);
+      return;
+    }
+
+    invoke<string>('get_thumbnail', { workingDirectory, path: image.path, size: THUMBNAIL_SIZE })
+      .then((thumbnail) => {
+        if (!cancelled) setSrc(convertFileSrc(thumbnail));
+      })
+      .catch((err) => {
+        console.warn(`Failed to create thumbnail for ${image.name}:`, err);
+        if (!cancelled) setSrc(image.thumbnailUrl);
+      });
+
+    return () => {
+      cancelled = true;
+    };
+  }, [workingDirectory, image.path, image.name, image.thumbnailUrl, image.modified]);
+
+  return src;
+}
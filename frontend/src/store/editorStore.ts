@@ -2,8 +2,16 @@ import { create } from 'zustand';
 import { immer } from 'zustand/middleware/immer';
 import { enableMapSet } from 'immer';
 import { nanoid } from 'nanoid';
-import { invoke } from '@tauri-apps/api/core';
-import type { CanvasObject, FrameSettings, TextObject, ImageObject, BackgroundObject, HeroObject } from '../types/canvas';
+import { invoke, convertFileSrc } from '@tauri-apps/api/core';
+import type {
+  CanvasObject,
+  FrameSettings,
+  TextObject,
+  ImageObject,
+  BackgroundObject,
+  HeroObject,
+  ShapeObject,
+} from '../types/canvas';
 
 // Enable Map/Set support in Immer
 enableMapSet();
@@ -11,7 +19,70 @@ enableMapSet();
 export interface ImageFile {
   path: string;
   name: string;
+  relativePath: string; // Relative to the working directory, e.g. "category/sku/1.jpg"
   thumbnailUrl: string;
+  // Read from file headers; null when the format is not recognized
+  width: number | null;
+  height: number | null;
+  fileSize: number | null;
+  modified: number | null; // Unix time, ms
+  colorType: string | null;
+  hasAlpha: boolean | null;
+  orientation: number | null; // EXIF orientation (1 = upright)
+}
+
+// Image entry as returned by the backend (`list_images_in_directory`, `images-changed`)
+export interface ImageFileInfo {
+  path: string;
+  name: string;
+  relative_path: string;
+  width: number | null;
+  height: number | null;
+  file_size: number | null;
+  modified: number | null;
+  color_type: string | null;
+  has_alpha: boolean | null;
+  orientation: number | null;
+}
+
+export function toImageFile(img: ImageFileInfo): ImageFile {
+  return {
+    path: img.path,
+    name: img.name,
+    relativePath: img.relative_path,
+    thumbnailUrl: convertFileSrc(img.path),
+    width: img.width,
+    height: img.height,
+    fileSize: img.file_size,
+    modified: img.modified,
+    colorType: img.color_type,
+    hasAlpha: img.has_alpha,
+    orientation: img.orientation,
+  };
+}
+
+// Image entry in the backend format, with the header metadata the renderer uses for memory estimates
+export function toImageFileInfo(img: ImageFile): ImageFileInfo {
+  return {
+    path: img.path,
+    name: img.name,
+    relative_path: img.relativePath,
+    width: img.width,
+    height: img.height,
+    file_size: img.fileSize,
+    modified: img.modified,
+    color_type: img.colorType,
+    has_alpha: img.hasAlpha,
+    orientation: img.orientation,
+  };
+}
+
+// Payload of the `images-changed` event
+export interface ImagesChanged {
+  added: ImageFileInfo[];
+  removed: string[];
+  changed: ImageFileInfo[];
+  stale_outputs: Record<string, string[]>; // Image path -> outputs generated before the change
 }
 
 export type AppPhase = 'startup' | 'gallery' | 'editor';
@@ -33,6 +104,22 @@ export interface TemplateInfo {
 export type TableRowData = Record<string, string>;
 export type TableData = Record<string, TableRowData>;
 
+// Table column with the hero focal point for cover ("fill") crops: "x,y" in percent of the image
+export const FOCAL_POINT_KEY = 'FOCUS';
+
+// Reads the column like generation does (`focal_point` in lib.rs): two numbers, each optionally followed by "%"
+export function parseFocalPoint(value: string | undefined): { x: number; y: number } | null {
+  const parts = (value ?? '').split(',');
+  if (parts.length !== 2) return null;
+  const [x, y] = parts.map((part) => {
+    const number = part.trim().replace(/%+$/, '');
+    return number === '' || number !== number.trim() ? NaN : Number(number);
+  });
+  if (!Number.isFinite(x) || !Number.isFinite(y)) return null;
+  const clamp = (v: number) => Math.min(100, Math.max(0, v));
+  return { x: clamp(x), y: clamp(y) };
+}
+
 interface EditorState {
   // App phase
   appPhase: AppPhase;
@@ -45,6 +132,9 @@ interface EditorState {
   allImages: ImageFile[];
   selectedImagePaths: Set<string>;
 
+  // Images modified on disk since their table row was last generated: path -> stale output files
+  staleImages: Record<string, string[]>;
+
   // Active tab in editor
   activeTab: EditorTab;
 
@@ -84,6 +174,8 @@ interface EditorActions {
 
   // Images
   setAllImages: (images: ImageFile[]) => void;
+  applyImageChanges: (changes: ImagesChanged) => void;
+  clearStaleImages: (paths: string[]) => void;
   toggleImageSelection: (path: string) => void;
   selectAllImages: () => void;
   deselectAllImages: () => void;
@@ -107,6 +199,7 @@ interface EditorActions {
   // Objects
   addText: (text?: Partial<TextObject>) => void;
   addImage: (src: string, name?: string) => void;
+  addShape: (shape: ShapeObject['shape']) => void;
   setBackground: (src: string, originalPath: string) => void;
   updateObject: (id: string, updates: Partial<CanvasObject>) => void;
   deleteObject: (id: string) => void;
@@ -187,6 +280,7 @@ export const useEditorStore = create<EditorState & EditorActions>()(
     workingDirectoryName: null,
     allImages: [],
     selectedImagePaths: new Set<string>(),
+    staleImages: {},
     activeTab: 'editor' as EditorTab,
     isGalleryOverlayOpen: false,
     galleryMode: 'select' as GalleryMode,
@@ -211,22 +305,54 @@ export const useEditorStore = create<EditorState & EditorActions>()(
         state.workingDirectoryName = name;
       }),
 
-    clearWorkingDirectory: () =>
+    clearWorkingDirectory: () => {
+      invoke('unwatch_images').catch((error) => console.warn('Failed to stop watching images:', error));
       set((state) => {
         state.workingDirectory = null;
         state.workingDirectoryName = null;
         state.allImages = [];
         state.selectedImagePaths = new Set();
-      }),
+        state.staleImages = {};
+      });
+    },
 
     // Image actions
     setAllImages: (images) =>
       set((state) => {
         state.allImages = images;
+        state.staleImages = {};
         // Select all by default
         state.selectedImagePaths = new Set(images.map((img) => img.path));
       }),
 
+    // Apply a diff from the directory watcher (new images are not selected automatically)
+    applyImageChanges: (changes) =>
+      set((state) => {
+        const removed = new Set(changes.removed);
+        const changed = new Map(changes.changed.map((img) => [img.path, toImageFile(img)]));
+
+        state.allImages = state.allImages
+          .filter((img) => !removed.has(img.path))
+          .map((img) => changed.get(img.path) ?? img)
+          .concat(changes.added.map(toImageFile))
+          .sort((a, b) => a.relativePath.toLowerCase().localeCompare(b.relativePath.toLowerCase()));
+
+        removed.forEach((path) => {
+          state.selectedImagePaths.delete(path);
+          delete state.staleImages[path];
+        });
+        changed.forEach((_, path) => {
+          state.staleImages[path] = changes.stale_outputs[path] ?? [];
+        });
+      }),
+
+    clearStaleImages: (paths) =>
+      set((state) => {
+        paths.forEach((path) => {
+          delete state.staleImages[path];
+        });
+      }),
+
     toggleImageSelection: (path) =>
       set((state) => {
         const newSet = new Set(state.selectedImagePaths);
@@ -354,6 +480,32 @@ export const useEditorStore = create<EditorState & EditorActions>()(
         state.selectedIds = [id];
       }),
 
+    addShape: (shape) =>
+      set((state) => {
+        const id = nanoid();
+        const existingShapes = state.objects.filter((o) => o.type === 'shape').length;
+        const newShape: ShapeObject = {
+          id,
+          type: 'shape',
+          x: 100,
+          y: 100,
+          width: 200,
+          height: shape === 'line' ? 20 : 200,
+          rotation: 0,
+          opacity: 1,
+          locked: false,
+          visible: true,
+          name: `Shape ${existingShapes + 1}`,
+          shape,
+          fill: shape === 'line' ? undefined : '#d9d9d9',
+          stroke: shape === 'line' ? '#000000' : undefined,
+          strokeWidth: shape === 'line' ? 4 : 0,
+          cornerRadius: 0,
+        };
+        state.objects.push(newShape);
+        state.selectedIds = [id];
+      }),
+
     setBackground: (src, originalPath) =>
       set((state) => {
         // Remove existing background
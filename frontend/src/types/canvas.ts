@@ -1,7 +1,7 @@
 // Базовый тип для всех объектов на canvas
 export interface BaseObject {
   id: string;
-  type: 'hero' | 'background' | 'text' | 'image';
+  type: 'hero' | 'background' | 'text' | 'image' | 'shape';
   x: number;
   y: number;
   width: number;
@@ -11,25 +11,76 @@ export interface BaseObject {
   locked: boolean;
   visible: boolean;
   name: string;
+  mask?: LayerMask;
+  clipToBelow?: boolean; // Показывать только там, где непрозрачен слой ниже (основа группы обрезки)
+}
+
+// Маска слоя: занимает рамку слоя и поворачивается вместе с ним (применяется при генерации в Rust)
+export interface LayerMask {
+  type: 'shape' | 'alpha' | 'luminance'; // Фигура, прозрачность или яркость изображения src
+  src?: string; // Путь к изображению маски (растягивается на рамку)
+  shape?: 'rect' | 'ellipse';
+  cornerRadius?: number; // Для прямоугольника
+  invert?: boolean;
+}
+
+// Коррекция изображения (применяется при генерации в Rust, по порядку списка)
+export type AdjustmentType = 'brightness' | 'contrast' | 'saturation' | 'hue' | 'grayscale' | 'blur';
+
+export interface Adjustment {
+  type: AdjustmentType;
+  value: number; // -100..100 % (яркость, контраст, насыщенность), градусы (оттенок), 0..100 % (ч/б), px (размытие)
+}
+
+export interface AdjustmentSettings {
+  adjustments?: Adjustment[];
+}
+
+// Тень слоя
+export interface ShadowSettings {
+  shadowColor?: string; // Тень включена, если задан цвет
+  shadowBlur?: number;
+  shadowOffsetX?: number;
+  shadowOffsetY?: number;
+  shadowOpacity?: number;
+}
+
+// Скругление углов, обводка (внутри края) и тень изображения
+export interface DecorationSettings extends ShadowSettings {
+  cornerRadius?: number;
+  stroke?: string;
+  strokeWidth?: number;
+}
+
+// Удаление однотонного фона (выполняется при генерации в Rust)
+export interface KeyingSettings {
+  keyBackground?: boolean;
+  keyTolerance?: number; // Допуск по цвету, % (по умолчанию 8)
+  keyFeather?: number; // Растушёвка края, px (по умолчанию 1)
 }
 
 // Главное изображение инфографики (нельзя удалить, можно заменить)
-export interface HeroObject extends BaseObject {
+export interface HeroObject extends BaseObject, KeyingSettings, AdjustmentSettings, DecorationSettings {
   type: 'hero';
   src: string;
   originalPath: string; // Путь к оригинальному файлу для замены
+  scaleMode?: 'fill' | 'fit' | 'stretch'; // По умолчанию 'fit'
+  align?: 'left' | 'center' | 'right'; // Выравнивание внутри рамки (по умолчанию по центру)
+  verticalAlign?: 'top' | 'middle' | 'bottom';
+  autoTrim?: boolean; // Обрезать прозрачные/однотонные поля перед вписыванием (при генерации)
+  trimPadding?: number; // Отступ вокруг содержимого, % от его большей стороны
 }
 
 // Фоновое изображение
-export interface BackgroundObject extends BaseObject {
+export interface BackgroundObject extends BaseObject, AdjustmentSettings {
   type: 'background';
   src: string;
   originalPath: string; // Путь к файлу для генерации в Rust
   scaleMode: 'fill' | 'fit' | 'stretch';
 }
 
-// Текстовый блок
-export interface TextObject extends BaseObject {
+// Текстовый блок (тень отбрасывает подложка, если она есть, иначе буквы с обводкой)
+export interface TextObject extends BaseObject, ShadowSettings {
   type: 'text';
   key: string; // Уникальный ключ для маппинга данных (TEXT-1, TEXT-2, ...)
   content: string;
@@ -38,19 +89,51 @@ export interface TextObject extends BaseObject {
   fontWeight: 'normal' | 'bold';
   fontStyle: 'normal' | 'italic';
   fill: string;
+  fillGradient?: Gradient; // Заменяет цвет заливки (фрагменты с [color=…] сохраняют свой цвет)
   align: 'left' | 'center' | 'right';
   verticalAlign: 'top' | 'middle' | 'bottom';
   lineHeight: number;
+  markup?: boolean; // Разметка в значениях таблицы: **жирный**, [color=#…]…[/color] и т. д. (по умолчанию выключена)
+  stroke?: string; // Обводка букв наружу
+  strokeWidth?: number;
+  backgroundColor?: string; // Подложка под текстом, если задан цвет
+  backgroundPadding?: number;
+  cornerRadius?: number; // Скругление подложки (не задано — полностью скруглённая «пилюля»)
 }
 
 // Дополнительное изображение (иконка)
-export interface ImageObject extends BaseObject {
+export interface ImageObject extends BaseObject, KeyingSettings, AdjustmentSettings, DecorationSettings {
   type: 'image';
   src: string;
 }
 
+// Фигура (прямоугольник, эллипс или линия по центру рамки); обводка центрирована по контуру
+export interface ShapeObject extends BaseObject {
+  type: 'shape';
+  shape: 'rect' | 'ellipse' | 'line';
+  fill?: string; // Не задан — без заливки
+  fillGradient?: Gradient; // Заменяет цвет заливки
+  stroke?: string; // Для линии — её цвет
+  strokeWidth?: number;
+  cornerRadius?: number; // Для прямоугольника
+}
+
 // Union тип для всех объектов
-export type CanvasObject = HeroObject | BackgroundObject | TextObject | ImageObject;
+export type CanvasObject = HeroObject | BackgroundObject | TextObject | ImageObject | ShapeObject;
+
+// Градиентная заливка (семантика CSS; при генерации применяется дизеринг против полос)
+export interface GradientStop {
+  color: string;
+  position: number; // 0..1
+}
+
+export interface Gradient {
+  type: 'linear' | 'radial';
+  angle?: number; // Линейный: градусы, 0 — снизу вверх, 90 — слева направо (по умолчанию 180 — сверху вниз)
+  stops: GradientStop[];
+  centerX?: number; // Радиальный: центр, % ширины (по умолчанию 50)
+  centerY?: number; // % высоты
+}
 
 // Настройки холста (Frame)
 export interface FrameSettings {
@@ -58,6 +141,8 @@ export interface FrameSettings {
   width: number;
   height: number;
   backgroundColor: string;
+  backgroundGradient?: Gradient; // Рисуется поверх цвета фона
+  fallbackFonts?: string[]; // Семейства для символов, которых нет в шрифте слоя (до системных)
 }
 
 // Aspect ratio presets
@@ -0,0 +1,96 @@
+use image::RgbaImage;
+use imageproc::filter::gaussian_blur_f32;
+use serde::{Deserialize, Serialize};
+
+pub const ADJUSTMENTS_BYTES_PER_PIXEL: u64 = 36; // Adjusted copy plus the float buffers of a blur
+
+// One entry of a layer's adjustments list; entries are applied in list order.
+// `kind`: "brightness" | "contrast" | "saturation" (value -100..100, percent change, CSS filter semantics),
+// "hue" (value in degrees), "grayscale" (value 0..100 percent), "blur" (value = radius in layer pixels).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Adjustment {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub value: f64,
+}
+
+// Apply adjustments to a layer image. `scale` is source pixels per layer pixel (for blur radii).
+pub fn apply_adjustments(image: &mut RgbaImage, adjustments: &[Adjustment], scale: f64) {
+    for adjustment in adjustments {
+        let amount = adjustment.value / 100.0;
+        match adjustment.kind.as_str() {
+            "brightness" => map_channels(image, |v| v * (1.0 + amount).max(0.0)),
+            "contrast" => map_channels(image, |v| (v - 0.5) * (1.0 + amount).max(0.0) + 0.5),
+            "saturation" => apply_matrix(image, &saturation_matrix((1.0 + amount).max(0.0))),
+            "grayscale" => apply_matrix(image, &saturation_matrix(1.0 - amount.clamp(0.0, 1.0))),
+            "hue" => apply_matrix(image, &hue_matrix(adjustment.value)),
+            "blur" => blur(image, adjustment.value * scale),
+            _ => {} // Unknown adjustments (e.g. from a newer editor) are skipped
+        }
+    }
+}
+
+// Helper: Apply the same curve to R, G and B (values 0..1) through a lookup table
+fn map_channels(image: &mut RgbaImage, curve: impl Fn(f64) -> f64) {
+    let table: Vec<u8> = (0..=255u8)
+        .map(|v| (curve(v as f64 / 255.0) * 255.0).round().clamp(0.0, 255.0) as u8)
+        .collect();
+    for pixel in image.pixels_mut() {
+        for c in 0..3 {
+            pixel[c] = table[pixel[c] as usize];
+        }
+    }
+}
+
+// Helper: Multiply RGB by a 3×3 color matrix (row-major)
+fn apply_matrix(image: &mut RgbaImage, m: &[f64; 9]) {
+    for pixel in image.pixels_mut() {
+        let (r, g, b) = (pixel[0] as f64, pixel[1] as f64, pixel[2] as f64);
+        for c in 0..3 {
+            let v = m[c * 3] * r + m[c * 3 + 1] * g + m[c * 3 + 2] * b;
+            pixel[c] = v.round().clamp(0.0, 255.0) as u8;
+        }
+    }
+}
+
+// Helper: Saturation matrix from the Filter Effects spec (0 = grayscale, 1 = unchanged)
+fn saturation_matrix(s: f64) -> [f64; 9] {
+    [
+        0.213 + 0.787 * s, 0.715 - 0.715 * s, 0.072 - 0.072 * s,
+        0.213 - 0.213 * s, 0.715 + 0.285 * s, 0.072 - 0.072 * s,
+        0.213 - 0.213 * s, 0.715 - 0.715 * s, 0.072 + 0.928 * s,
+    ]
+}
+
+// Helper: Hue rotation matrix from the Filter Effects spec
+fn hue_matrix(degrees: f64) -> [f64; 9] {
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    [
+        0.213 + cos * 0.787 - sin * 0.213, 0.715 - cos * 0.715 - sin * 0.715, 0.072 - cos * 0.072 + sin * 0.928,
+        0.213 - cos * 0.213 + sin * 0.143, 0.715 + cos * 0.285 + sin * 0.140, 0.072 - cos * 0.072 - sin * 0.283,
+        0.213 - cos * 0.213 - sin * 0.787, 0.715 - cos * 0.715 + sin * 0.715, 0.072 + cos * 0.928 + sin * 0.072,
+    ]
+}
+
+// Helper: Gaussian blur with premultiplied alpha (transparent pixels don't darken edges).
+// The radius follows the CSS shadow convention (sigma = radius / 2), like shadows.
+fn blur(image: &mut RgbaImage, radius: f64) {
+    let sigma = (radius / 2.0) as f32;
+    if sigma <= 0.01 {
+        return;
+    }
+
+    for pixel in image.pixels_mut() {
+        let a = pixel[3] as u32;
+        for c in 0..3 {
+            pixel[c] = ((pixel[c] as u32 * a + 127) / 255) as u8;
+        }
+    }
+    *image = gaussian_blur_f32(image, sigma);
+    for pixel in image.pixels_mut().filter(|p| p[3] > 0) {
+        let a = pixel[3] as u32;
+        for c in 0..3 {
+            pixel[c] = ((pixel[c] as u32 * 255 + a / 2) / a).min(255) as u8;
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use image::{Rgba, RgbaImage};
+
+// Canvas-space raster of a single rendered layer (straight alpha), placed at (x, y) on the canvas
+pub struct Patch {
+    pub x: i64,
+    pub y: i64,
+    pub pixels: RgbaImage,
+}
+
+impl Patch {
+    // Build a patch from premultiplied RGBA floats (0.0..=1.0), row-major, width * height pixels
+    pub fn from_premultiplied(x: i64, y: i64, width: u32, height: u32, data: &[[f32; 4]]) -> Self {
+        Patch {
+            x,
+            y,
+            pixels: image_from_premultiplied(width, height, data),
+        }
+    }
+}
+
+// Build a straight-alpha image from premultiplied RGBA floats, row-major
+pub fn image_from_premultiplied(width: u32, height: u32, data: &[[f32; 4]]) -> RgbaImage {
+    let mut pixels = RgbaImage::new(width, height);
+    for (pixel, src) in pixels.pixels_mut().zip(data.iter()) {
+        *pixel = unpremultiply(*src);
+    }
+    pixels
+}
+
+// Convert a straight-alpha color into premultiplied floats, scaled by coverage (0.0..=1.0)
+pub fn premultiply(color: Rgba<u8>, coverage: f32) -> [f32; 4] {
+    let a = color[3] as f32 / 255.0 * coverage;
+    [
+        color[0] as f32 / 255.0 * a,
+        color[1] as f32 / 255.0 * a,
+        color[2] as f32 / 255.0 * a,
+        a,
+    ]
+}
+
+// Source-over of premultiplied floats
+pub fn over(dst: &mut [f32; 4], src: [f32; 4]) {
+    for c in 0..4 {
+        dst[c] = src[c] + dst[c] * (1.0 - src[3]);
+    }
+}
+
+// Convert a premultiplied float pixel into a straight-alpha 8-bit pixel
+pub fn unpremultiply(p: [f32; 4]) -> Rgba<u8> {
+    let a = p[3].clamp(0.0, 1.0);
+    if a <= 0.0 {
+        return Rgba([0, 0, 0, 0]);
+    }
+    let to_u8 = |v: f32| ((v / a).clamp(0.0, 1.0) * 255.0 + 0.5) as u8;
+    Rgba([to_u8(p[0]), to_u8(p[1]), to_u8(p[2]), (a * 255.0 + 0.5) as u8])
+}
+
+// Source-over blend of a single straight-alpha pixel onto the canvas
+pub fn blend_pixel(dst: &mut Rgba<u8>, src: Rgba<u8>, opacity: f32) {
+    let sa = src[3] as f32 / 255.0 * opacity;
+    if sa <= 0.0 {
+        return;
+    }
+    let da = dst[3] as f32 / 255.0;
+    let out_a = sa + da * (1.0 - sa);
+    if out_a <= 0.0 {
+        return;
+    }
+
+    for c in 0..3 {
+        let s = src[c] as f32 / 255.0;
+        let d = dst[c] as f32 / 255.0;
+        let v = (s * sa + d * da * (1.0 - sa)) / out_a;
+        dst[c] = (v.clamp(0.0, 1.0) * 255.0 + 0.5) as u8;
+    }
+    dst[3] = (out_a.clamp(0.0, 1.0) * 255.0 + 0.5) as u8;
+}
+
+// Composite a patch onto the canvas with the given layer opacity, clipping to canvas bounds
+pub fn draw_patch(canvas: &mut RgbaImage, patch: &Patch, opacity: f64) {
+    let opacity = opacity.clamp(0.0, 1.0) as f32;
+    if opacity <= 0.0 {
+        return;
+    }
+
+    let (canvas_width, canvas_height) = canvas.dimensions();
+
+    for (px, py, pixel) in patch.pixels.enumerate_pixels() {
+        let target_x = patch.x + px as i64;
+        let target_y = patch.y + py as i64;
+
+        if target_x >= 0
+            && target_y >= 0
+            && target_x < canvas_width as i64
+            && target_y < canvas_height as i64
+        {
+            blend_pixel(
+                canvas.get_pixel_mut(target_x as u32, target_y as u32),
+                *pixel,
+                opacity,
+            );
+        }
+    }
+}
+
+// Composite a patch onto another patch (clipped to it), e.g. to group the parts of a layer before applying its opacity
+pub fn merge_patch(target: &mut Patch, patch: &Patch) {
+    let (width, height) = target.pixels.dimensions();
+    for (px, py, pixel) in patch.pixels.enumerate_pixels() {
+        let x = patch.x + px as i64 - target.x;
+        let y = patch.y + py as i64 - target.y;
+        if x >= 0 && y >= 0 && x < width as i64 && y < height as i64 {
+            blend_pixel(target.pixels.get_pixel_mut(x as u32, y as u32), *pixel, 1.0);
+        }
+    }
+}
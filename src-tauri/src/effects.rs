@@ -0,0 +1,68 @@
+use crate::compose::Patch;
+use image::{GrayImage, Luma, Rgba, RgbaImage};
+use imageproc::filter::gaussian_blur_f32;
+
+// Drop shadow settings (offsets and blur radius in layer pixels)
+#[derive(Debug, Clone, Copy)]
+pub struct Shadow {
+    pub color: Rgba<u8>,
+    pub offset_x: f64,
+    pub offset_y: f64,
+    pub blur: f64,
+}
+
+impl Shadow {
+    // How far the shadow can reach beyond the shape casting it
+    pub fn extent(&self) -> f64 {
+        self.offset_x.abs().max(self.offset_y.abs()) + self.blur.max(0.0) * 1.5
+    }
+
+    // Blur and offset a silhouette mask rendered at `scale` mask pixels per layer pixel.
+    // The blur radius follows the CSS convention (sigma = radius / 2).
+    pub fn cast(&self, silhouette: &GrayImage, scale: f64) -> GrayImage {
+        let sigma = (self.blur * scale / 2.0) as f32;
+        let blurred = if sigma > 0.01 {
+            gaussian_blur_f32(silhouette, sigma)
+        } else {
+            silhouette.clone()
+        };
+
+        let dx = (self.offset_x * scale).round() as i64;
+        let dy = (self.offset_y * scale).round() as i64;
+        let (width, height) = blurred.dimensions();
+
+        GrayImage::from_fn(width, height, |x, y| {
+            let sx = x as i64 - dx;
+            let sy = y as i64 - dy;
+            if sx >= 0 && sy >= 0 && sx < width as i64 && sy < height as i64 {
+                *blurred.get_pixel(sx as u32, sy as u32)
+            } else {
+                Luma([0])
+            }
+        })
+    }
+
+    // Shadow of an already rendered (rotated) layer, cast from its alpha in canvas space
+    pub fn cast_patch(&self, patch: &Patch) -> Patch {
+        let margin = self.extent().ceil() as u32 + 1;
+        let (width, height) = patch.pixels.dimensions();
+        let silhouette = GrayImage::from_fn(width + 2 * margin, height + 2 * margin, |x, y| {
+            match (x.checked_sub(margin), y.checked_sub(margin)) {
+                (Some(px), Some(py)) if px < width && py < height => Luma([patch.pixels.get_pixel(px, py)[3]]),
+                _ => Luma([0]),
+            }
+        });
+
+        let mask = self.cast(&silhouette, 1.0);
+        let [r, g, b, a] = self.color.0;
+        let pixels = RgbaImage::from_fn(mask.width(), mask.height(), |x, y| {
+            let coverage = mask.get_pixel(x, y)[0] as u32;
+            Rgba([r, g, b, ((a as u32 * coverage + 127) / 255) as u8])
+        });
+        Patch {
+            x: patch.x - margin as i64,
+            y: patch.y - margin as i64,
+            pixels,
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use image::ImageError;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io;
+
+// Pipeline step an image failed at
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorStage {
+    Decode, // Reading the source image
+    Render, // Composing layers
+    Encode, // Compressing the output
+    Write,  // Saving the output file
+}
+
+// What went wrong, independent of the stage
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    NotFound,
+    PermissionDenied,
+    UnsupportedFormat,
+    CorruptData,
+    TooLarge, // Exceeds decoder limits or available memory
+    DiskFull,
+    Io,
+    Internal,
+}
+
+impl ErrorKind {
+    pub fn from_io(error: &io::Error) -> ErrorKind {
+        match error.kind() {
+            io::ErrorKind::NotFound => ErrorKind::NotFound,
+            io::ErrorKind::PermissionDenied | io::ErrorKind::ReadOnlyFilesystem => ErrorKind::PermissionDenied,
+            io::ErrorKind::StorageFull | io::ErrorKind::QuotaExceeded => ErrorKind::DiskFull,
+            io::ErrorKind::OutOfMemory | io::ErrorKind::FileTooLarge => ErrorKind::TooLarge,
+            io::ErrorKind::InvalidData | io::ErrorKind::UnexpectedEof => ErrorKind::CorruptData,
+            _ => ErrorKind::Io,
+        }
+    }
+
+    pub fn from_image(error: &ImageError) -> ErrorKind {
+        match error {
+            ImageError::IoError(e) => ErrorKind::from_io(e),
+            ImageError::Unsupported(_) => ErrorKind::UnsupportedFormat,
+            ImageError::Decoding(_) => ErrorKind::CorruptData,
+            ImageError::Limits(_) => ErrorKind::TooLarge,
+            ImageError::Encoding(_) | ImageError::Parameter(_) => ErrorKind::Internal,
+        }
+    }
+}
+
+// Error with its classification (before the image and stage it belongs to are known)
+#[derive(Debug, Clone)]
+pub struct ClassifiedError {
+    pub kind: ErrorKind,
+    pub message: String,
+}
+
+impl ClassifiedError {
+    pub fn new(kind: ErrorKind, message: impl fmt::Display) -> ClassifiedError {
+        ClassifiedError {
+            kind,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ClassifiedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl From<io::Error> for ClassifiedError {
+    fn from(error: io::Error) -> Self {
+        ClassifiedError::new(ErrorKind::from_io(&error), error)
+    }
+}
+
+impl From<ImageError> for ClassifiedError {
+    fn from(error: ImageError) -> Self {
+        ClassifiedError::new(ErrorKind::from_image(&error), error)
+    }
+}
+
+impl From<ClassifiedError> for String {
+    fn from(error: ClassifiedError) -> Self {
+        error.message
+    }
+}
+
+// Failure of one image in a batch (reported in GenerateResult and kept in the job log)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GenerationError {
+    pub path: String, // Source image
+    pub name: String,
+    pub stage: ErrorStage,
+    pub kind: ErrorKind,
+    pub message: String,
+}
+
+impl GenerationError {
+    pub fn new(path: &str, name: &str, stage: ErrorStage, error: ClassifiedError) -> GenerationError {
+        GenerationError {
+            path: path.to_string(),
+            name: name.to_string(),
+            stage,
+            kind: error.kind,
+            message: error.message,
+        }
+    }
+}
@@ -0,0 +1,498 @@
+use crate::woff2;
+use font_kit::family_name::FamilyName;
+use font_kit::handle::Handle;
+use font_kit::properties::{Properties, Weight};
+use font_kit::source::SystemSource;
+use rustybuzz::ttf_parser;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+
+// Font file extensions picked up from the bundled fonts folder
+const FONT_EXTENSIONS: &[&str] = &["ttf", "otf", "ttc", "otc", "woff2"];
+
+// Tried in order when a text layer has no font family set
+const DEFAULT_FAMILIES: &[&str] = &["Arial", "Helvetica"];
+
+// CSS generic family keywords (resolved to the default families)
+const GENERIC_FAMILIES: &[&str] = &["system-ui", "sans-serif", "serif", "monospace", "cursive", "fantasy", "ui-sans-serif"];
+
+// System families checked first when looking for a font that covers missing characters
+const FALLBACK_FAMILIES: &[&str] = &[
+    "Arial",
+    "Helvetica",
+    "Noto Sans",
+    "DejaVu Sans",
+    "Segoe UI",
+    "Segoe UI Symbol",
+    "Noto Sans Symbols",
+    "Noto Sans Symbols 2",
+    "Arial Unicode MS",
+    "Noto Sans CJK SC",
+    "PingFang SC",
+    "Hiragino Sans",
+    "Microsoft YaHei",
+    "Yu Gothic",
+    "Malgun Gothic",
+    "Noto Sans Arabic",
+    "Noto Sans Devanagari",
+    "Noto Sans Hebrew",
+    "Noto Sans Thai",
+    "Apple Color Emoji",
+    "Noto Color Emoji",
+    "Segoe UI Emoji",
+];
+
+// Installed family covering a character, with its position in the fallback order
+type FallbackFamily = (usize, String);
+
+// First installed family covering each character (None = no installed font does).
+// Finding it means reading installed fonts one by one, so each character is looked up once per session.
+static SYSTEM_FALLBACKS: OnceLock<Mutex<HashMap<char, Option<FallbackFamily>>>> = OnceLock::new();
+
+// A font file (or a face inside a collection)
+#[derive(Clone)]
+pub struct FontFile {
+    pub bytes: Vec<u8>,
+    pub index: u32,
+    pub bold: bool, // Face has a bold weight (no synthetic emboldening needed)
+    name: String,   // PostScript name, used to avoid loading the same face twice
+}
+
+// A face shipped in the working directory's fonts folder
+pub struct BundledFont {
+    pub family: String,
+    italic: bool,
+    file: FontFile,
+}
+
+// Load every face from the bundled fonts folder (sorted by family).
+// Files that cannot be read are skipped and reported as warnings.
+pub fn load_bundled_fonts(dir: &Path) -> (Vec<BundledFont>, Vec<String>) {
+    let mut fonts: Vec<BundledFont> = Vec::new();
+    let mut warnings: Vec<String> = Vec::new();
+
+    let Ok(entries) = fs::read_dir(dir) else {
+        return (fonts, warnings);
+    };
+
+    let mut paths: Vec<_> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_file())
+        .filter(|p| {
+            p.extension()
+                .map(|ext| FONT_EXTENSIONS.contains(&ext.to_string_lossy().to_lowercase().as_str()))
+                .unwrap_or(false)
+        })
+        .collect();
+    paths.sort();
+
+    for path in paths {
+        let file_name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        match load_font_file(&path) {
+            Ok(faces) if faces.is_empty() => warnings.push(format!("Skipped font {}: no usable faces", file_name)),
+            Ok(faces) => fonts.extend(faces),
+            Err(e) => warnings.push(format!("Skipped font {}: {}", file_name, e)),
+        }
+    }
+
+    fonts.sort_by_key(|f| f.family.to_lowercase());
+    (fonts, warnings)
+}
+
+// Helper: Read all faces of a font file (WOFF2 is converted to a plain font first)
+fn load_font_file(path: &Path) -> Result<Vec<BundledFont>, String> {
+    let mut bytes = fs::read(path).map_err(|e| e.to_string())?;
+    if woff2::is_woff2(&bytes) {
+        bytes = woff2::decode(&bytes)?;
+    }
+
+    let count = ttf_parser::fonts_in_collection(&bytes).unwrap_or(1);
+    let mut faces = Vec::new();
+    for index in 0..count {
+        let face = ttf_parser::Face::parse(&bytes, index).map_err(|e| e.to_string())?;
+        let name = |id: u16| {
+            face.names()
+                .into_iter()
+                .filter(|n| n.name_id == id && n.is_unicode())
+                .find_map(|n| n.to_string())
+        };
+        let Some(family) = name(ttf_parser::name_id::TYPOGRAPHIC_FAMILY).or_else(|| name(ttf_parser::name_id::FAMILY)) else {
+            continue;
+        };
+        let postscript_name = name(ttf_parser::name_id::POST_SCRIPT_NAME).unwrap_or_else(|| format!("{}-{}", family, index));
+
+        faces.push(BundledFont {
+            italic: face.is_italic() || face.is_oblique(),
+            file: FontFile {
+                bytes: bytes.clone(), // Faces of a collection each keep a copy of the file
+                index,
+                bold: face.weight().to_number() >= 600,
+                name: postscript_name,
+            },
+            family,
+        });
+    }
+
+    Ok(faces)
+}
+
+// Fallback chain selector: a layer's font family and weight
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FontKey {
+    pub family: String, // Empty = default sans-serif
+    pub bold: bool,
+}
+
+// Fonts resolved once per batch: unique font files and the fallback chain of every layer font
+pub struct FontLibrary {
+    pub files: Vec<FontFile>,
+    chains: HashMap<FontKey, Vec<usize>>,
+}
+
+impl FontLibrary {
+    // Fallback chain (indices into `files`, highest priority first)
+    pub fn chain(&self, key: &FontKey) -> &[usize] {
+        self.chains.get(key).map(|c| c.as_slice()).unwrap_or(&[])
+    }
+
+    // Build fallback chains for every layer font: the layer font itself, then user-configured
+    // fallbacks, then bundled and installed fonts that cover characters the former lack.
+    // Bundled fonts take precedence over installed fonts of the same family.
+    // Returns warnings for characters that no installed font can render.
+    pub fn resolve(
+        requirements: &HashMap<FontKey, BTreeSet<char>>,
+        fallback_families: &[String],
+        bundled: &[BundledFont],
+    ) -> Result<(FontLibrary, Vec<String>), String> {
+        let mut resolver = Resolver {
+            bundled,
+            source: SystemSource::new(),
+            files: Vec::new(),
+            loaded: HashMap::new(),
+        };
+
+        let default_key = FontKey {
+            family: String::new(),
+            bold: false,
+        };
+        let mut keys: Vec<&FontKey> = requirements.keys().collect();
+        if !requirements.contains_key(&default_key) {
+            keys.push(&default_key);
+        }
+        keys.sort_by(|a, b| (&a.family, a.bold).cmp(&(&b.family, b.bold)));
+
+        // Layer fonts and user-configured fallbacks
+        let mut chains: HashMap<FontKey, Vec<usize>> = HashMap::new();
+        for key in keys {
+            let mut chain = Vec::new();
+            if let Some(primary) = resolver.primary(key) {
+                chain.push(primary);
+            }
+            for family in fallback_families {
+                if let Some(index) = resolver.installed(family, false) {
+                    if !chain.contains(&index) {
+                        chain.push(index);
+                    }
+                }
+            }
+            chains.insert(key.clone(), chain);
+        }
+
+        if chains.get(&default_key).is_none_or(|c| c.is_empty()) {
+            return Err("No system fonts available".to_string());
+        }
+
+        // Characters the chains cannot render yet
+        let mut missing: HashMap<FontKey, BTreeSet<char>> = HashMap::new();
+        for (key, chars) in requirements {
+            let faces: Vec<ttf_parser::Face> = chains[key].iter().filter_map(|&i| resolver.files[i].face()).collect();
+            let uncovered: BTreeSet<char> = chars
+                .iter()
+                .copied()
+                .filter(|c| !is_default_ignorable(*c))
+                .filter(|c| !faces.iter().any(|face| face.glyph_index(*c).is_some()))
+                .collect();
+            if !uncovered.is_empty() {
+                missing.insert(key.clone(), uncovered);
+            }
+        }
+
+        // Bundled fonts that cover what is still missing; faces that don't are dropped right away
+        if !missing.is_empty() {
+            let mut seen = HashSet::new();
+            for font in bundled {
+                if missing.is_empty() {
+                    break;
+                }
+                if seen.insert(font.family.to_lowercase()) {
+                    if let Some(file) = resolver.bundled_face(&font.family, false) {
+                        resolver.cover(&mut missing, &mut chains, file);
+                    }
+                }
+            }
+        }
+
+        // Installed fonts: only the families that cover a missing character are loaded into the batch
+        if !missing.is_empty() {
+            let remaining: BTreeSet<char> = missing.values().flatten().copied().collect();
+            for (family, covered) in system_fallbacks(&resolver.source, &remaining) {
+                let Some(index) = resolver.system_face(&family) else {
+                    continue;
+                };
+                for (key, uncovered) in missing.iter_mut() {
+                    let before = uncovered.len();
+                    uncovered.retain(|c| !covered.contains(c));
+                    if uncovered.len() < before {
+                        let chain = chains.get_mut(key).expect("chain exists for every key");
+                        if !chain.contains(&index) {
+                            chain.push(index);
+                        }
+                    }
+                }
+                missing.retain(|_, uncovered| !uncovered.is_empty());
+            }
+        }
+
+        // One warning per layer family (regular and bold share it)
+        let mut unrenderable: BTreeMap<&str, BTreeSet<char>> = BTreeMap::new();
+        for (key, uncovered) in &missing {
+            let family = if key.family.is_empty() { "default font" } else { key.family.as_str() };
+            unrenderable.entry(family).or_default().extend(uncovered);
+        }
+        let warnings: Vec<String> = unrenderable
+            .iter()
+            .map(|(family, uncovered)| {
+                let chars = uncovered
+                    .iter()
+                    .map(|c| format!("'{}' (U+{:04X})", c, *c as u32))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("No installed font covers {} used with {}", chars, family)
+            })
+            .collect();
+
+        Ok((
+            FontLibrary {
+                files: resolver.files,
+                chains,
+            },
+            warnings,
+        ))
+    }
+}
+
+impl FontFile {
+    // Parsed tables, used to check character coverage
+    fn face(&self) -> Option<ttf_parser::Face<'_>> {
+        ttf_parser::Face::parse(&self.bytes, self.index).ok()
+    }
+}
+
+// Characters that never need a glyph of their own (joiners, variation selectors, controls)
+fn is_default_ignorable(c: char) -> bool {
+    c.is_control()
+        || matches!(c,
+            '\u{200B}'..='\u{200F}'
+            | '\u{202A}'..='\u{202E}'
+            | '\u{2060}'..='\u{2064}'
+            | '\u{FE00}'..='\u{FE0F}'
+            | '\u{FEFF}'
+            | '\u{E0100}'..='\u{E01EF}')
+}
+
+// Loads bundled and system fonts for one batch, deduplicating identical faces
+struct Resolver<'a> {
+    bundled: &'a [BundledFont],
+    source: SystemSource,
+    files: Vec<FontFile>,
+    loaded: HashMap<(String, bool), Option<usize>>,
+}
+
+impl Resolver<'_> {
+    // The layer font, falling back to the default sans-serif families.
+    // The family may be a CSS-style list ("Inter, system-ui, sans-serif"); generic names are skipped.
+    fn primary(&mut self, key: &FontKey) -> Option<usize> {
+        let mut families: Vec<&str> = key
+            .family
+            .split(',')
+            .map(|f| f.trim().trim_matches(|c| c == '"' || c == '\''))
+            .filter(|f| !f.is_empty() && !GENERIC_FAMILIES.contains(&f.to_lowercase().as_str()))
+            .collect();
+        families.extend(DEFAULT_FAMILIES);
+
+        for family in &families {
+            if key.bold {
+                if let Some(index) = self.installed(family, true) {
+                    return Some(index);
+                }
+            }
+            if let Some(index) = self.installed(family, false) {
+                return Some(index);
+            }
+        }
+
+        // Whatever the system considers sans-serif
+        let file = load_font(&self.source, &[FamilyName::SansSerif], key.bold)
+            .or_else(|| load_font(&self.source, &[FamilyName::SansSerif], false))?;
+        Some(self.add(file))
+    }
+
+    // A face of a bundled or installed family (bold = only if a bold face exists)
+    fn installed(&mut self, family: &str, bold: bool) -> Option<usize> {
+        let cache_key = (family.to_string(), bold);
+        if let Some(cached) = self.loaded.get(&cache_key) {
+            return *cached;
+        }
+
+        let index = match self.bundled_face(family, bold) {
+            Some(file) => Some(self.add(file)),
+            None => load_font(&self.source, &[FamilyName::Title(family.to_string())], bold)
+                .filter(|file| !bold || file.bold)
+                .map(|file| self.add(file)),
+        };
+        self.loaded.insert(cache_key, index);
+        index
+    }
+
+    // Upright bundled face of a family with the requested weight (regular falls back to any weight)
+    fn bundled_face(&self, family: &str, bold: bool) -> Option<FontFile> {
+        let faces: Vec<&BundledFont> = self
+            .bundled
+            .iter()
+            .filter(|f| f.family.eq_ignore_ascii_case(family))
+            .collect();
+        let pick = |bold: bool| faces.iter().find(|f| f.file.bold == bold && !f.italic);
+
+        let face = if bold { pick(true) } else { pick(false).or_else(|| pick(true)).or(faces.first()) };
+        face.map(|f| f.file.clone())
+    }
+
+    // Regular face of an installed family, ignoring bundled fonts of the same name
+    fn system_face(&mut self, family: &str) -> Option<usize> {
+        let file = load_font(&self.source, &[FamilyName::Title(family.to_string())], false)?;
+        Some(self.add(file))
+    }
+
+    // Add `file` to the chains still missing characters it covers; it is only kept if it covers any
+    fn cover(
+        &mut self,
+        missing: &mut HashMap<FontKey, BTreeSet<char>>,
+        chains: &mut HashMap<FontKey, Vec<usize>>,
+        file: FontFile,
+    ) {
+        let Some(face) = file.face() else {
+            return;
+        };
+        let covering: Vec<(FontKey, BTreeSet<char>)> = missing
+            .iter()
+            .map(|(key, uncovered)| {
+                let covered: BTreeSet<char> = uncovered.iter().copied().filter(|c| face.glyph_index(*c).is_some()).collect();
+                (key.clone(), covered)
+            })
+            .filter(|(_, covered)| !covered.is_empty())
+            .collect();
+        if covering.is_empty() {
+            return;
+        }
+
+        let index = self.add(file);
+        for (key, covered) in covering {
+            let chain = chains.get_mut(&key).expect("chain exists for every key");
+            if !chain.contains(&index) {
+                chain.push(index);
+            }
+            if let Some(uncovered) = missing.get_mut(&key) {
+                uncovered.retain(|c| !covered.contains(c));
+            }
+        }
+        missing.retain(|_, uncovered| !uncovered.is_empty());
+    }
+
+    fn add(&mut self, file: FontFile) -> usize {
+        if let Some(existing) = self.files.iter().position(|f| f.name == file.name) {
+            return existing;
+        }
+        self.files.push(file);
+        self.files.len() - 1
+    }
+}
+
+// Installed families covering `chars`, in fallback order (preferred families, then all others by name).
+// Lookups are cached in SYSTEM_FALLBACKS; each font read while scanning is dropped before the next one.
+fn system_fallbacks(source: &SystemSource, chars: &BTreeSet<char>) -> Vec<(String, BTreeSet<char>)> {
+    let mut cache = SYSTEM_FALLBACKS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+
+    let mut unknown: BTreeSet<char> = chars.iter().copied().filter(|c| !cache.contains_key(c)).collect();
+    if !unknown.is_empty() {
+        let mut candidates: Vec<String> = FALLBACK_FAMILIES.iter().map(|f| f.to_string()).collect();
+        if let Ok(mut families) = source.all_families() {
+            families.retain(|f| !f.starts_with('.'));
+            families.sort();
+            candidates.extend(families);
+        }
+
+        let mut seen = HashSet::new();
+        for (rank, family) in candidates.into_iter().enumerate() {
+            if unknown.is_empty() {
+                break;
+            }
+            if !seen.insert(family.to_lowercase()) {
+                continue;
+            }
+            let Some(file) = load_font(source, &[FamilyName::Title(family.clone())], false) else {
+                continue;
+            };
+            let Some(face) = file.face() else {
+                continue;
+            };
+            unknown.retain(|c| {
+                let covered = face.glyph_index(*c).is_some();
+                if covered {
+                    cache.insert(*c, Some((rank, family.clone())));
+                }
+                !covered
+            });
+        }
+        for c in unknown {
+            cache.insert(c, None);
+        }
+    }
+
+    let mut families: BTreeMap<FallbackFamily, BTreeSet<char>> = BTreeMap::new();
+    for c in chars {
+        if let Some(Some(family)) = cache.get(c) {
+            families.entry(family.clone()).or_default().insert(*c);
+        }
+    }
+    families.into_iter().map(|((_, family), chars)| (family, chars)).collect()
+}
+
+// Load the best matching face among the given families
+fn load_font(source: &SystemSource, families: &[FamilyName], bold: bool) -> Option<FontFile> {
+    let mut properties = Properties::new();
+    if bold {
+        properties.weight(Weight::BOLD);
+    }
+
+    let handle = source.select_best_match(families, &properties).ok()?;
+
+    // Faces inside collections (.ttc) are addressed by index
+    let index = match &handle {
+        Handle::Path { font_index, .. } | Handle::Memory { font_index, .. } => *font_index,
+    };
+    let font = handle.load().ok()?;
+    let bytes = font.copy_font_data()?.to_vec();
+
+    Some(FontFile {
+        bytes,
+        index,
+        bold: font.properties().weight >= Weight::SEMIBOLD,
+        name: font.postscript_name().unwrap_or_else(|| font.full_name()),
+    })
+}
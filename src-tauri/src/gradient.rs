@@ -0,0 +1,214 @@
+use crate::compose::{over, premultiply, unpremultiply};
+use image::{Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
+
+// 8×8 Bayer matrix for ordered dithering (values 0..63)
+const BAYER: [[u8; 8]; 8] = [
+    [0, 32, 8, 40, 2, 34, 10, 42],
+    [48, 16, 56, 24, 50, 18, 58, 26],
+    [12, 44, 4, 36, 14, 46, 6, 38],
+    [60, 28, 52, 20, 62, 30, 54, 22],
+    [3, 35, 11, 43, 1, 33, 9, 41],
+    [51, 19, 59, 27, 49, 17, 57, 25],
+    [15, 47, 7, 39, 13, 45, 5, 37],
+    [63, 31, 55, 23, 61, 29, 53, 21],
+];
+
+// One color stop of a gradient (`position` 0..1 along the gradient line or radius)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GradientStop {
+    pub color: String,
+    pub position: f64,
+}
+
+// Gradient fill with CSS semantics.
+// `kind`: "linear" (`angle` in degrees, 0 = bottom to top, 90 = left to right, default 180 = top to bottom)
+// | "radial" (ellipse reaching the farthest corner, centered at `center_x`/`center_y` percent of the box, default 50).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Gradient {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub angle: Option<f64>,
+    pub stops: Vec<GradientStop>,
+    pub center_x: Option<f64>,
+    pub center_y: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Geometry {
+    Linear { dx: f64, dy: f64, length: f64 }, // Unit direction and gradient line length, through the box center
+    Radial { rx: f64, ry: f64 },              // Ellipse radii
+}
+
+// A gradient resolved for a box of a given size, ready to sample in box-local coordinates
+#[derive(Debug, Clone)]
+pub struct GradientFill {
+    geometry: Geometry,
+    center: (f64, f64),
+    stops: Vec<(f64, [f32; 4])>, // Sorted positions with premultiplied colors
+}
+
+impl GradientFill {
+    pub fn new(gradient: &Gradient, width: f64, height: f64, parse_color: impl Fn(&str) -> Rgba<u8>) -> Self {
+        let mut stops: Vec<(f64, [f32; 4])> = gradient
+            .stops
+            .iter()
+            .map(|stop| (stop.position.clamp(0.0, 1.0), premultiply(parse_color(&stop.color), 1.0)))
+            .collect();
+        stops.sort_by(|a, b| a.0.total_cmp(&b.0)); // Stable, so stops at the same position make a hard edge
+
+        let (center, geometry) = if gradient.kind == "radial" {
+            let cx = width * gradient.center_x.unwrap_or(50.0) / 100.0;
+            let cy = height * gradient.center_y.unwrap_or(50.0) / 100.0;
+            // Same aspect ratio as the farthest-side ellipse, scaled to pass through the farthest corner
+            let rx = cx.max(width - cx) * std::f64::consts::SQRT_2;
+            let ry = cy.max(height - cy) * std::f64::consts::SQRT_2;
+            ((cx, cy), Geometry::Radial { rx, ry })
+        } else {
+            let (sin, cos) = gradient.angle.unwrap_or(180.0).to_radians().sin_cos();
+            let (dx, dy) = (sin, -cos);
+            // CSS gradient line: long enough for the corners to get the end colors
+            let length = (width * dx).abs() + (height * dy).abs();
+            ((width / 2.0, height / 2.0), Geometry::Linear { dx, dy, length })
+        };
+
+        GradientFill { geometry, center, stops }
+    }
+
+    // Premultiplied color at a box-local point
+    pub fn sample(&self, lx: f64, ly: f64) -> [f32; 4] {
+        let px = lx - self.center.0;
+        let py = ly - self.center.1;
+        let t = match self.geometry {
+            Geometry::Linear { dx, dy, length } if length > 0.0 => (px * dx + py * dy) / length + 0.5,
+            Geometry::Linear { .. } => 0.5,
+            Geometry::Radial { rx, ry } if rx > 0.0 && ry > 0.0 => (px / rx).hypot(py / ry),
+            Geometry::Radial { .. } => 0.0,
+        };
+        self.color_at(t)
+    }
+
+    // Premultiplied color at a box-local point with ordered dithering for canvas pixel (x, y),
+    // so 8-bit output doesn't band on long, low-contrast gradients
+    pub fn sample_dithered(&self, lx: f64, ly: f64, x: i64, y: i64) -> [f32; 4] {
+        let mut color = self.sample(lx, ly);
+        let noise = dither(x, y) * color[3];
+        for value in color.iter_mut().take(3) {
+            *value = (*value + noise).max(0.0);
+        }
+        color
+    }
+
+    // Paint the gradient over a whole image (pixel centers map to box-local coordinates)
+    pub fn paint(&self, image: &mut RgbaImage) {
+        for (x, y, pixel) in image.enumerate_pixels_mut() {
+            let mut out = premultiply(*pixel, 1.0);
+            over(&mut out, self.sample_dithered(x as f64 + 0.5, y as f64 + 0.5, x as i64, y as i64));
+            *pixel = unpremultiply(out);
+        }
+    }
+
+    // Helper: Interpolate the stops in premultiplied space (CSS), clamped to the end colors
+    fn color_at(&self, t: f64) -> [f32; 4] {
+        let Some(&(first_position, first)) = self.stops.first() else {
+            return [0.0; 4];
+        };
+        if t <= first_position {
+            return first;
+        }
+        for pair in self.stops.windows(2) {
+            let ((p0, c0), (p1, c1)) = (pair[0], pair[1]);
+            if t <= p1 {
+                let f = if p1 > p0 { ((t - p0) / (p1 - p0)) as f32 } else { 1.0 };
+                return std::array::from_fn(|c| c0[c] + (c1[c] - c0[c]) * f);
+            }
+        }
+        self.stops[self.stops.len() - 1].1
+    }
+}
+
+// Helper: Dither offset for a canvas pixel, within ±half an 8-bit step
+fn dither(x: i64, y: i64) -> f32 {
+    let threshold = BAYER[y.rem_euclid(8) as usize][x.rem_euclid(8) as usize] as f32;
+    ((threshold + 0.5) / 64.0 - 0.5) / 255.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_hex_color;
+
+    fn gradient(kind: &str, angle: f64, stops: &[(&str, f64)]) -> Gradient {
+        Gradient {
+            kind: kind.to_string(),
+            angle: Some(angle),
+            stops: stops
+                .iter()
+                .map(|&(color, position)| GradientStop {
+                    color: color.to_string(),
+                    position,
+                })
+                .collect(),
+            center_x: None,
+            center_y: None,
+        }
+    }
+
+    fn assert_color(actual: [f32; 4], expected: &str) {
+        let expected = premultiply(parse_hex_color(expected), 1.0);
+        assert!(actual.iter().zip(expected).all(|(a, e)| (a - e).abs() < 1.0 / 255.0), "{:?} != {:?}", actual, expected);
+    }
+
+    #[test]
+    fn linear_gradient_follows_css_angles() {
+        let stops = [("#ff0000", 0.0), ("#0000ff", 1.0)];
+        let left_to_right = GradientFill::new(&gradient("linear", 90.0, &stops), 100.0, 50.0, parse_hex_color);
+        assert_color(left_to_right.sample(0.0, 10.0), "#ff0000");
+        assert_color(left_to_right.sample(100.0, 40.0), "#0000ff");
+        assert_color(left_to_right.sample(50.0, 0.0), "#800080");
+
+        // Default angle: top to bottom; corners get the end colors
+        let top_to_bottom = GradientFill::new(&gradient("linear", 180.0, &stops), 100.0, 50.0, parse_hex_color);
+        assert_color(top_to_bottom.sample(0.0, 0.0), "#ff0000");
+        assert_color(top_to_bottom.sample(100.0, 50.0), "#0000ff");
+    }
+
+    #[test]
+    fn radial_gradient_reaches_the_farthest_corner() {
+        let fill = GradientFill::new(&gradient("radial", 0.0, &[("#ffffff", 0.0), ("#000000", 1.0)]), 100.0, 50.0, parse_hex_color);
+        assert_color(fill.sample(50.0, 25.0), "#ffffff");
+        assert_color(fill.sample(100.0, 50.0), "#000000");
+        assert_color(fill.sample(0.0, 0.0), "#000000");
+    }
+
+    #[test]
+    fn stops_at_the_same_position_make_a_hard_edge() {
+        let stops = [("#ff0000", 0.0), ("#ff0000", 0.5), ("#0000ff", 0.5), ("#0000ff", 1.0)];
+        let fill = GradientFill::new(&gradient("linear", 90.0, &stops), 100.0, 10.0, parse_hex_color);
+        assert_color(fill.sample(49.0, 5.0), "#ff0000");
+        assert_color(fill.sample(51.0, 5.0), "#0000ff");
+    }
+
+    #[test]
+    fn dither_offsets_are_balanced_within_half_a_step() {
+        let offsets: Vec<f32> = (0..8).flat_map(|y| (0..8).map(move |x| dither(x, y))).collect();
+        assert!(offsets.iter().all(|offset| offset.abs() < 0.5 / 255.0));
+        assert!(offsets.iter().sum::<f32>().abs() < 1e-6);
+        assert_eq!(dither(-3, -5), dither(5, 3));
+    }
+
+    #[test]
+    fn dithering_mixes_neighboring_levels_instead_of_banding() {
+        // Two 8-bit steps over 512 pixels: without dithering, three flat bands
+        let fill = GradientFill::new(&gradient("linear", 90.0, &[("#000000", 0.0), ("#020202", 1.0)]), 512.0, 8.0, parse_hex_color);
+        let mut image = RgbaImage::from_pixel(512, 8, Rgba([0, 0, 0, 255]));
+        fill.paint(&mut image);
+
+        // Around x = 128 the exact value is half a step: pixels alternate between 0 and 1 and average to it
+        let block: Vec<u8> = (124..132).flat_map(|x| (0..8).map(move |y| (x, y))).map(|(x, y)| image.get_pixel(x, y)[0]).collect();
+        assert!(block.contains(&0) && block.contains(&1));
+        let mean = block.iter().map(|&v| v as f64).sum::<f64>() / block.len() as f64;
+        assert!((mean - 0.5).abs() < 0.1, "mean {}", mean);
+    }
+}
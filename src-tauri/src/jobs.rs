@@ -0,0 +1,234 @@
+use crate::errors::GenerationError;
+use crate::{GenerateRequest, ImageFileInfo};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// A job is stored as two files in the jobs folder:
+// `<id>.json` - the generation request, written once when the job starts;
+// `<id>.log` - one JSON line per finished image, appended as images complete.
+// A crash loses at most the line being written; unlogged images are simply generated again.
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageStatus {
+    Done,
+    Failed,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JobFile {
+    id: String,
+    created_at: u64, // Unix time, milliseconds
+    request: GenerateRequest,
+}
+
+#[derive(Serialize, Deserialize)]
+struct LogEntry {
+    path: String,
+    status: ImageStatus,
+    #[serde(default)]
+    error: Option<GenerationError>,
+}
+
+// Job overview for the UI
+#[derive(Serialize, Debug)]
+pub struct JobSummary {
+    id: String,
+    created_at: u64,
+    template_name: Option<String>,
+    total: usize,
+    done: usize,
+    pub failed: usize,
+    pub pending: usize,
+}
+
+pub struct Job {
+    pub id: String,
+    created_at: u64,
+    pub request: GenerateRequest,
+    finished: HashMap<String, ImageStatus>, // Source path -> last logged status
+    dir: PathBuf,
+}
+
+impl Job {
+    // Persist a new job before any image is processed
+    pub fn create(dir: &Path, request: GenerateRequest) -> Result<Job, String> {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+
+        // Millisecond timestamps are unique enough; bump on the rare collision
+        let mut id = format!("{:x}", created_at);
+        let mut suffix = 1;
+        while dir.join(format!("{}.json", id)).exists() {
+            id = format!("{:x}-{}", created_at, suffix);
+            suffix += 1;
+        }
+
+        let file = JobFile {
+            id: id.clone(),
+            created_at,
+            request,
+        };
+        let json = serde_json::to_vec(&file).map_err(|e| e.to_string())?;
+        let temp = dir.join(format!("{}.json.tmp", id));
+        fs::write(&temp, json)
+            .and_then(|_| fs::rename(&temp, dir.join(format!("{}.json", id))))
+            .map_err(|e| format!("Failed to save job: {}", e))?;
+
+        Ok(Job {
+            id,
+            created_at,
+            request: file.request,
+            finished: HashMap::new(),
+            dir: dir.to_path_buf(),
+        })
+    }
+
+    pub fn load(dir: &Path, id: &str) -> Result<Job, String> {
+        let data = fs::read(dir.join(format!("{}.json", id))).map_err(|e| format!("Failed to read job {}: {}", id, e))?;
+        let file: JobFile = serde_json::from_slice(&data).map_err(|e| format!("Invalid job {}: {}", id, e))?;
+
+        let mut finished = HashMap::new();
+        if let Ok(log) = File::open(dir.join(format!("{}.log", id))) {
+            // A line cut off by a crash fails to parse and is skipped
+            for entry in BufReader::new(log)
+                .lines()
+                .map_while(Result::ok)
+                .filter_map(|line| serde_json::from_str::<LogEntry>(&line).ok())
+            {
+                finished.insert(entry.path, entry.status);
+            }
+        }
+
+        Ok(Job {
+            id: file.id,
+            created_at: file.created_at,
+            request: file.request,
+            finished,
+            dir: dir.to_path_buf(),
+        })
+    }
+
+    // Images not yet processed, in the original order
+    pub fn pending_images(&self) -> Vec<ImageFileInfo> {
+        self.request
+            .selected_images
+            .iter()
+            .filter(|image| !self.finished.contains_key(&image.path))
+            .cloned()
+            .collect()
+    }
+
+    // Images whose last attempt failed, in the original order
+    pub fn failed_images(&self) -> Vec<ImageFileInfo> {
+        self.request
+            .selected_images
+            .iter()
+            .filter(|image| self.finished.get(&image.path) == Some(&ImageStatus::Failed))
+            .cloned()
+            .collect()
+    }
+
+    pub fn summary(&self) -> JobSummary {
+        let total = self.request.selected_images.len();
+        let count = |status: ImageStatus| {
+            self.request
+                .selected_images
+                .iter()
+                .filter(|image| self.finished.get(&image.path) == Some(&status))
+                .count()
+        };
+        let done = count(ImageStatus::Done);
+        let failed = count(ImageStatus::Failed);
+
+        JobSummary {
+            id: self.id.clone(),
+            created_at: self.created_at,
+            template_name: self.request.template_name.clone(),
+            total,
+            done,
+            failed,
+            pending: total - done - failed,
+        }
+    }
+
+    pub fn open_log(&self) -> Result<JobLog, String> {
+        let path = self.dir.join(format!("{}.log", self.id));
+        let torn = fs::read(&path).map(|data| data.last().is_some_and(|&b| b != b'\n')).unwrap_or(false);
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| format!("Failed to open job log: {}", e))?;
+        // Terminate a line cut off by a crash so the next entry starts on its own line
+        if torn {
+            file.write_all(b"\n").map_err(|e| format!("Failed to open job log: {}", e))?;
+        }
+        Ok(JobLog {
+            file: Mutex::new(file),
+            dir: self.dir.clone(),
+            id: self.id.clone(),
+        })
+    }
+}
+
+// Append-only record of finished images, shared by all workers of a job
+pub struct JobLog {
+    file: Mutex<File>,
+    dir: PathBuf,
+    id: String,
+}
+
+impl JobLog {
+    pub fn record(&self, path: &str, status: ImageStatus, error: Option<&GenerationError>) {
+        let entry = LogEntry {
+            path: path.to_string(),
+            status,
+            error: error.cloned(),
+        };
+        if let (Ok(mut line), Ok(mut file)) = (serde_json::to_vec(&entry), self.file.lock()) {
+            line.push(b'\n');
+            let _ = file.write_all(&line).and_then(|_| file.flush());
+        }
+    }
+
+    // Delete the job once nothing is left to process or retry
+    pub fn remove(self) {
+        drop(self.file);
+        remove_job(&self.dir, &self.id);
+    }
+}
+
+// Jobs with images left to process or retry, newest first
+pub fn list_unfinished_jobs(dir: &Path) -> Vec<JobSummary> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut jobs: Vec<JobSummary> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            name.strip_suffix(".json").map(|id| id.to_string())
+        })
+        .filter_map(|id| Job::load(dir, &id).ok())
+        .map(|job| job.summary())
+        .filter(|summary| summary.pending > 0 || summary.failed > 0)
+        .collect();
+
+    jobs.sort_by_key(|job| std::cmp::Reverse(job.created_at));
+    jobs
+}
+
+pub fn remove_job(dir: &Path, id: &str) {
+    let _ = fs::remove_file(dir.join(format!("{}.json", id)));
+    let _ = fs::remove_file(dir.join(format!("{}.log", id)));
+}
@@ -0,0 +1,122 @@
+use image::{GrayImage, Luma, Rgba, RgbaImage};
+use imageproc::filter::gaussian_blur_f32;
+use std::collections::{HashMap, VecDeque};
+
+const MIN_BORDER_SHARE: f64 = 0.3; // Share of border pixels the dominant color needs for the background to count as uniform
+const BUCKET_BITS: u32 = 4; // Per-channel precision of the border color histogram
+pub const KEYING_BYTES_PER_PIXEL: u64 = 11; // Background and foreground masks plus the float blur buffers
+
+// Background keying settings (tolerance 0..1 of the channel range, feather in source pixels)
+#[derive(Debug, Clone, Copy)]
+pub struct Keying {
+    pub tolerance: f64,
+    pub feather: f64,
+}
+
+// Remove a near-uniform background connected to the image borders (e.g. a white studio backdrop).
+// Returns false (image untouched) when the border has no dominant color to key out.
+pub fn key_out_background(image: &mut RgbaImage, keying: &Keying) -> bool {
+    let Some(background) = border_color(image) else {
+        return false;
+    };
+    let background_mask = flood_from_borders(image, background, keying.tolerance);
+
+    // Foreground alpha: 1 inside the object, easing to 0 over the feather width at the cut
+    let sigma = (keying.feather / 2.0) as f32; // CSS convention, like shadows
+    let foreground = GrayImage::from_fn(image.width(), image.height(), |x, y| {
+        Luma([if background_mask.get_pixel(x, y)[0] > 0 { 0 } else { 255 }])
+    });
+    let soft = if sigma > 0.01 {
+        gaussian_blur_f32(&foreground, sigma)
+    } else {
+        foreground
+    };
+
+    // Blurring spreads the mask outwards too; only the inner half of the ramp is kept (stretched to 0..1),
+    // so anti-aliased edge pixels still tinted by the background end up mostly transparent
+    for (x, y, pixel) in image.enumerate_pixels_mut() {
+        let coverage = if background_mask.get_pixel(x, y)[0] > 0 {
+            0.0
+        } else {
+            ((soft.get_pixel(x, y)[0] as f64 / 255.0 - 0.5) * 2.0).clamp(0.0, 1.0)
+        };
+        if coverage <= 0.0 {
+            *pixel = Rgba([0, 0, 0, 0]);
+        } else {
+            pixel[3] = (pixel[3] as f64 * coverage).round() as u8;
+        }
+    }
+    true
+}
+
+// Dominant border color (average of its histogram bucket), None when no color dominates
+pub fn border_color(image: &RgbaImage) -> Option<[f64; 3]> {
+    let (width, height) = image.dimensions();
+    if width < 3 || height < 3 {
+        return None;
+    }
+
+    let border = (0..width)
+        .flat_map(|x| [(x, 0), (x, height - 1)])
+        .chain((1..height - 1).flat_map(|y| [(0, y), (width - 1, y)]));
+    let shift = 8 - BUCKET_BITS;
+    let mut buckets: HashMap<u32, ([f64; 3], usize)> = HashMap::new();
+    let mut total = 0;
+    for (x, y) in border {
+        let p = image.get_pixel(x, y);
+        total += 1;
+        if p[3] < 128 {
+            continue; // Already transparent
+        }
+        let key = ((p[0] as u32 >> shift) << (2 * BUCKET_BITS)) | ((p[1] as u32 >> shift) << BUCKET_BITS) | (p[2] as u32 >> shift);
+        let entry = buckets.entry(key).or_insert(([0.0; 3], 0));
+        for c in 0..3 {
+            entry.0[c] += p[c] as f64;
+        }
+        entry.1 += 1;
+    }
+
+    let (sum, count) = buckets.into_values().max_by_key(|(_, count)| *count)?;
+    if (count as f64) < total as f64 * MIN_BORDER_SHARE {
+        return None;
+    }
+    Some(sum.map(|s| s / count as f64))
+}
+
+// Helper: Pixels within tolerance of the background color that are connected to the border (255 = background)
+fn flood_from_borders(image: &RgbaImage, background: [f64; 3], tolerance: f64) -> GrayImage {
+    let (width, height) = image.dimensions();
+    let limit = tolerance.clamp(0.0, 1.0) * 255.0;
+    let matches = |x: u32, y: u32| {
+        let p = image.get_pixel(x, y);
+        p[3] < 128 || (0..3).all(|c| (p[c] as f64 - background[c]).abs() <= limit)
+    };
+
+    let mut mask = GrayImage::new(width, height);
+    let mut queue = VecDeque::new();
+    let border = (0..width)
+        .flat_map(|x| [(x, 0), (x, height - 1)])
+        .chain((0..height).flat_map(|y| [(0, y), (width - 1, y)]));
+    for (x, y) in border {
+        if mask.get_pixel(x, y)[0] == 0 && matches(x, y) {
+            mask.put_pixel(x, y, Luma([255]));
+            queue.push_back((x, y));
+        }
+    }
+
+    while let Some((x, y)) = queue.pop_front() {
+        let neighbors = [
+            (x.wrapping_sub(1), y),
+            (x + 1, y),
+            (x, y.wrapping_sub(1)),
+            (x, y + 1),
+        ];
+        for (nx, ny) in neighbors {
+            if nx < width && ny < height && mask.get_pixel(nx, ny)[0] == 0 && matches(nx, ny) {
+                mask.put_pixel(nx, ny, Luma([255]));
+                queue.push_back((nx, ny));
+            }
+        }
+    }
+    mask
+}
@@ -1,24 +1,77 @@
-use ab_glyph::{Font, FontVec, PxScale, ScaleFont};
+mod adjustments;
+mod compose;
+mod effects;
+mod errors;
+mod fonts;
+mod gradient;
+mod jobs;
+mod keying;
+mod loader;
+mod markup;
+mod masks;
+mod memory;
+mod metadata;
+mod progress;
+mod scan;
+mod shapes;
+mod text;
+mod thumbnails;
+mod transform;
+mod trim;
+mod watcher;
+mod woff2;
+
+use adjustments::{apply_adjustments, Adjustment, ADJUSTMENTS_BYTES_PER_PIXEL};
+use compose::{draw_patch, merge_patch, Patch};
+use effects::Shadow;
+use errors::{ClassifiedError, ErrorKind, ErrorStage, GenerationError};
 use font_kit::source::SystemSource;
+use fonts::{load_bundled_fonts, FontKey, FontLibrary};
 use futures::stream::{self, StreamExt};
-use image::{DynamicImage, ImageBuffer, Rgba, RgbaImage};
-use imageproc::drawing::draw_text_mut;
-use imageproc::geometric_transformations::{rotate_about_center, Interpolation};
-use serde::Deserialize;
-use std::collections::HashMap;
+use gradient::{Gradient, GradientFill};
+use image::{DynamicImage, ImageBuffer, ImageFormat, Rgba, RgbaImage};
+use jobs::{ImageStatus, Job, JobSummary};
+use keying::{key_out_background, Keying, KEYING_BYTES_PER_PIXEL};
+use loader::{is_svg, load_image, LoadOptions, TargetSize};
+use markup::{parse_markup, plain_text, TextRun};
+use masks::{apply_mask, MaskMode};
+use memory::{estimate_memory_mb, layer_bytes, memory_budget_mb};
+use metadata::ImageMetadata;
+use progress::ProgressTracker;
+use scan::{scan_images, ScanOptions};
+use serde::{Deserialize, Serialize};
+use shapes::{render_shape, ShapeKind, ShapeStyle};
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap};
 use std::fs;
+use std::io::Cursor;
 use std::path::{Path, PathBuf};
-use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::Arc;
-use tauri::{AppHandle, Emitter};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, State};
+use tokio::sync::Semaphore;
+use text::{draw_text_layer, LoadedFonts, TextBackground, TextStyle};
+use thumbnails::{get_or_create_thumbnail, remove_stale_thumbnails};
+use transform::{fit_rect, render_image_clipped, ImageFit, LayerFrame, LocalRect, Placement};
+use trim::trim_to_content;
+use watcher::{watch_directory, DirectoryWatcher};
 
 const TEMPLATES_DIR: &str = ".infographics-templates";
 const OUTPUT_DIR: &str = "infographics";
+const FONTS_DIR: &str = ".infographics-fonts";
+const THUMBNAILS_DIR: &str = ".infographics-thumbnails";
+const JOBS_DIR: &str = ".infographics-jobs";
+const FOCAL_POINT_KEY: &str = "FOCUS"; // Table column with the hero focal point: "x,y" in percent of the image
+const TRIM_DECODE_HEADROOM: f64 = 2.0; // Trimmed heroes are decoded larger: their content is drawn bigger than the whole image
+const TRIM_BYTES_PER_PIXEL: u64 = 4; // Trimmed copy of the hero
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 struct ImageFileInfo {
     path: String,
     name: String,
+    #[serde(default)]
+    relative_path: String, // Relative to the working directory, `/`-separated
+    #[serde(flatten, default)]
+    metadata: ImageMetadata,
 }
 
 #[derive(serde::Serialize, serde::Deserialize)]
@@ -29,17 +82,18 @@ struct TemplateInfo {
 }
 
 // Structures for infographic generation
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 struct FrameSettings {
     width: u32,
     height: u32,
     background_color: String,
+    background_gradient: Option<Gradient>, // Painted over the background color
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
-#[allow(dead_code)] // Some fields reserved for future features (custom fonts, alignment)
+#[allow(dead_code)] // Some fields are only used by the editor
 struct CanvasObject {
     id: String,
     #[serde(rename = "type")]
@@ -60,12 +114,50 @@ struct CanvasObject {
     font_weight: Option<String>,
     fill: Option<String>,
     align: Option<String>,
+    markup: Option<bool>, // Text: parse **bold**, [color=…] etc. in the content (off by default)
     // Image-specific fields
     src: Option<String>,
     original_path: Option<String>,
+    scale_mode: Option<String>,     // Hero: "fit" (contain) | "fill" (cover) | "stretch"
+    vertical_align: Option<String>, // Hero: "top" | "middle" | "bottom" (horizontal uses `align`)
+    key_background: Option<bool>,   // Hero/image: remove a uniform background connected to the borders
+    key_tolerance: Option<f64>,     // Color distance still keyed out / trimmed, percent of the channel range (default 8)
+    key_feather: Option<f64>,       // Soft edge width at the cut, layer pixels (default 1)
+    auto_trim: Option<bool>,        // Hero: crop transparent or background-colored margins before fitting
+    trim_padding: Option<f64>,      // Hero: margin kept around the trimmed content, percent of its larger side
+    adjustments: Option<Vec<Adjustment>>, // Hero/image/background: color and blur adjustments, applied in order
+    // Shape-specific fields (fill and stroke are shared with text; stroke and corner radius with hero/image layers)
+    shape: Option<String>,       // "rect" | "ellipse" | "line"
+    stroke: Option<String>,
+    stroke_width: Option<f64>,
+    corner_radius: Option<f64>,
+    fill_gradient: Option<Gradient>, // Replaces the fill color
+    // Effects
+    shadow_color: Option<String>,
+    shadow_blur: Option<f64>,
+    shadow_offset_x: Option<f64>,
+    shadow_offset_y: Option<f64>,
+    shadow_opacity: Option<f64>,
+    background_color: Option<String>, // Text background box
+    background_padding: Option<f64>,
+    // Masking
+    mask: Option<LayerMask>,
+    clip_to_below: Option<bool>, // Show only where the group's base (nearest unclipped layer below) is opaque; a hidden base hides its group
 }
 
-#[derive(Deserialize, Debug)]
+// Mask of a layer, covering the layer box and rotating with it
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct LayerMask {
+    #[serde(rename = "type")]
+    kind: String,                // "shape" | "alpha" | "luminance" (of the `src` image, stretched to the box)
+    src: Option<String>,         // Mask image path
+    shape: Option<String>,       // "rect" | "ellipse"
+    corner_radius: Option<f64>,  // Rounded rect masks
+    invert: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 struct GenerateRequest {
     working_directory: String,
@@ -75,61 +167,108 @@ struct GenerateRequest {
     selected_images: Vec<ImageFileInfo>,
     template_name: Option<String>,  // Output subdirectory (if working with a template)
     parallelism: Option<usize>,     // Number of threads (default: half of CPU cores)
+    apply_exif_orientation: Option<bool>, // Rotate images per their EXIF tag (default: true)
+    fallback_fonts: Option<Vec<String>>, // Families tried before system fonts for missing characters
+    memory_budget_mb: Option<u64>, // Peak memory of concurrent images (default: half of available RAM)
 }
 
 #[derive(serde::Serialize)]
 struct GenerateResult {
     success: bool,
+    job_id: String, // For retry_failed
     generated_files: Vec<String>,
-    errors: Vec<String>,
+    errors: Vec<GenerationError>,
+    warnings: Vec<String>,
 }
 
-#[derive(Clone, serde::Serialize)]
-struct GenerationProgress {
-    current: usize,
-    total: usize,
-    current_file: String,
+// Output of a single processed image
+struct GeneratedImage {
+    path: String,
+    bytes: u64, // Size of the written file
+    warnings: Vec<String>,
 }
 
+// Walking subfolders and reading every header can take a while, so it runs off the main thread
 #[tauri::command]
-fn list_images_in_directory(directory: String) -> Result<Vec<ImageFileInfo>, String> {
-    let path = Path::new(&directory);
+async fn list_images_in_directory(directory: String, options: Option<ScanOptions>) -> Result<Vec<ImageFileInfo>, String> {
+    tokio::task::spawn_blocking(move || {
+        let path = Path::new(&directory);
 
-    if !path.is_dir() {
-        return Err("Not a valid directory".to_string());
-    }
+        if !path.is_dir() {
+            return Err("Not a valid directory".to_string());
+        }
 
-    let image_extensions = ["jpg", "jpeg", "png", "gif", "webp", "bmp", "svg"];
+        scan_images(path, &options.unwrap_or_default())
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
 
-    let mut images: Vec<ImageFileInfo> = Vec::new();
+// Watcher of the current working directory (replaced when another folder is opened)
+#[derive(Default)]
+struct WatcherState {
+    watcher: Mutex<Option<DirectoryWatcher>>,
+}
 
-    match fs::read_dir(path) {
-        Ok(entries) => {
-            for entry in entries.flatten() {
-                let file_path = entry.path();
-                if file_path.is_file() {
-                    if let Some(ext) = file_path.extension() {
-                        let ext_lower = ext.to_string_lossy().to_lowercase();
-                        if image_extensions.contains(&ext_lower.as_str()) {
-                            images.push(ImageFileInfo {
-                                path: file_path.to_string_lossy().to_string(),
-                                name: file_path
-                                    .file_name()
-                                    .map(|n| n.to_string_lossy().to_string())
-                                    .unwrap_or_default(),
-                            });
-                        }
-                    }
-                }
-            }
-        }
-        Err(e) => return Err(e.to_string()),
-    }
+// Emit `images-changed` whenever images are added, removed or modified in the directory.
+// `images` is the list already returned by `list_images_in_directory`; without it the folder is scanned again.
+#[tauri::command]
+async fn watch_images(
+    app: AppHandle,
+    state: State<'_, WatcherState>,
+    directory: String,
+    options: Option<ScanOptions>,
+    images: Option<Vec<ImageFileInfo>>,
+) -> Result<(), String> {
+    *state.watcher.lock().map_err(|e| e.to_string())? = None; // Stop watching the previous folder first
+
+    let watcher = tokio::task::spawn_blocking(move || {
+        watch_directory(PathBuf::from(directory), options.unwrap_or_default(), images, move |changes| {
+            let _ = app.emit("images-changed", changes);
+        })
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))??;
 
-    // Sort alphabetically by name
-    images.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    *state.watcher.lock().map_err(|e| e.to_string())? = Some(watcher);
+    Ok(())
+}
 
-    Ok(images)
+#[tauri::command]
+fn unwatch_images(state: State<'_, WatcherState>) -> Result<(), String> {
+    *state.watcher.lock().map_err(|e| e.to_string())? = None;
+    Ok(())
+}
+
+// Path of the cached thumbnail (longest side `size` px), generated on first request
+#[tauri::command]
+async fn get_thumbnail(working_directory: String, path: String, size: u32) -> Result<String, String> {
+    let cache_dir = Path::new(&working_directory).join(THUMBNAILS_DIR);
+    tokio::task::spawn_blocking(move || get_or_create_thumbnail(&cache_dir, Path::new(&path), size))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
+        .map(|thumbnail| thumbnail.to_string_lossy().to_string())
+}
+
+// Fill the thumbnail cache in the background (returns immediately)
+#[tauri::command]
+fn prefetch_thumbnails(working_directory: String, paths: Vec<String>, size: u32) {
+    let cache_dir = Arc::new(Path::new(&working_directory).join(THUMBNAILS_DIR));
+    let parallelism = (num_cpus::get() / 2).max(1);
+
+    tauri::async_runtime::spawn(async move {
+        stream::iter(paths.clone())
+            .map(|path| {
+                let cache_dir = cache_dir.clone();
+                tokio::task::spawn_blocking(move || get_or_create_thumbnail(&cache_dir, Path::new(&path), size))
+            })
+            .buffer_unordered(parallelism)
+            .for_each(|_| async {}) // Failures are retried (and reported) by get_thumbnail
+            .await;
+
+        // Thumbnails of earlier versions of the prefetched images
+        let _ = tokio::task::spawn_blocking(move || remove_stale_thumbnails(&cache_dir, &paths, size)).await;
+    });
 }
 
 #[derive(serde::Serialize)]
@@ -153,18 +292,13 @@ fn get_cpu_info() -> CpuInfo {
 }
 
 #[tauri::command]
-fn get_system_fonts() -> Vec<String> {
+fn get_system_fonts(working_directory: Option<String>) -> Vec<String> {
     let source = SystemSource::new();
-    match source.all_families() {
-        Ok(families) => {
-            let mut fonts: Vec<String> = families
-                .into_iter()
-                .filter(|f| !f.starts_with('.')) // Filter hidden fonts
-                .collect();
-            fonts.sort();
-            fonts.dedup();
-            fonts
-        }
+    let mut fonts: Vec<String> = match source.all_families() {
+        Ok(families) => families
+            .into_iter()
+            .filter(|f| !f.starts_with('.')) // Filter hidden fonts
+            .collect(),
         Err(_) => vec![
             "Arial".to_string(),
             "Helvetica".to_string(),
@@ -172,7 +306,17 @@ fn get_system_fonts() -> Vec<String> {
             "Georgia".to_string(),
             "Monaco".to_string(),
         ],
+    };
+
+    // Fonts bundled with the working directory
+    if let Some(dir) = working_directory {
+        let (bundled, _) = load_bundled_fonts(&Path::new(&dir).join(FONTS_DIR));
+        fonts.extend(bundled.into_iter().map(|f| f.family));
     }
+
+    fonts.sort();
+    fonts.dedup();
+    fonts
 }
 
 // Template commands
@@ -271,6 +415,13 @@ fn list_templates(working_directory: String) -> Result<Vec<TemplateInfo>, String
     Ok(templates)
 }
 
+// Helper: Names of the saved templates (each has its own output folder under `infographics/`)
+fn template_names(working_directory: &Path) -> Vec<String> {
+    list_templates(working_directory.to_string_lossy().to_string())
+        .map(|templates| templates.into_iter().map(|t| t.name).collect())
+        .unwrap_or_default()
+}
+
 #[tauri::command]
 fn delete_template(template_path: String) -> Result<(), String> {
     let path = Path::new(&template_path);
@@ -300,320 +451,404 @@ fn parse_hex_color(hex: &str) -> Rgba<u8> {
     }
 }
 
-// Helper: Rotate image around its center with expanded canvas to prevent clipping
-fn rotate_image(img: &RgbaImage, angle_degrees: f64) -> RgbaImage {
-    if angle_degrees == 0.0 {
-        return img.clone();
-    }
-
-    let (w, h) = img.dimensions();
-    let radians = angle_degrees.to_radians();
-    let cos = radians.cos().abs();
-    let sin = radians.sin().abs();
-
-    // Calculate expanded dimensions to fit rotated image
-    let new_w = (w as f64 * cos + h as f64 * sin).ceil() as u32;
-    let new_h = (w as f64 * sin + h as f64 * cos).ceil() as u32;
+// Helper: Layer frame of a canvas object (keeps fractional position and size)
+fn object_frame(obj: &CanvasObject) -> LayerFrame {
+    LayerFrame::new(obj.x, obj.y, obj.width, obj.height, obj.rotation)
+}
 
-    // Create expanded buffer
-    let mut expanded: RgbaImage = ImageBuffer::from_pixel(new_w, new_h, Rgba([0, 0, 0, 0]));
+// Helper: Borrow image as RGBA8, converting only when necessary
+fn as_rgba8(source: &DynamicImage) -> Cow<'_, RgbaImage> {
+    match source.as_rgba8() {
+        Some(rgba) => Cow::Borrowed(rgba),
+        None => Cow::Owned(source.to_rgba8()),
+    }
+}
 
-    // Center the original image in the expanded buffer
-    let offset_x = (new_w - w) / 2;
-    let offset_y = (new_h - h) / 2;
+// Rounded corners, border and drop shadow of an image layer
+#[derive(Default)]
+struct ImageDecoration {
+    corner_radius: f64,
+    stroke: Option<Rgba<u8>>, // Border inside the image edge
+    stroke_width: f64,
+    shadow: Option<Shadow>, // Cast by the image's alpha after rotation
+}
 
-    for (px, py, pixel) in img.enumerate_pixels() {
-        expanded.put_pixel(px + offset_x, py + offset_y, *pixel);
+// Helper: Canvas-sized image of a layer's mask (None when the mask image can't be loaded)
+fn render_mask(mask: &LayerMask, obj: &CanvasObject, load_options: LoadOptions, frame: &FrameSettings) -> Option<RgbaImage> {
+    let mut mask_image = RgbaImage::new(frame.width, frame.height);
+    if mask.kind == "shape" {
+        let style = ShapeStyle {
+            kind: ShapeKind::parse(mask.shape.as_deref()),
+            fill: Some(Rgba([255, 255, 255, 255])),
+            fill_gradient: None,
+            stroke: None,
+            stroke_width: 0.0,
+            corner_radius: mask.corner_radius.unwrap_or(0.0),
+        };
+        if let Some(patch) = render_shape(&style, &object_frame(obj), mask_image.dimensions()) {
+            draw_patch(&mut mask_image, &patch, 1.0);
+        }
+    } else {
+        let src = mask.src.as_deref()?;
+        let image = load_image(Path::new(src), load_options, TargetSize::Stretch(obj.width, obj.height)).ok()?;
+        overlay_image(&mut mask_image, &image, &object_frame(obj), &ImageDecoration::default(), 1.0);
     }
+    Some(mask_image)
+}
 
-    // Now rotate - the expanded buffer is large enough to contain rotated corners
-    rotate_about_center(&expanded, radians as f32, Interpolation::Bilinear, Rgba([0, 0, 0, 0]))
+// Helper: Decoration of a hero or image layer
+fn image_decoration(obj: &CanvasObject) -> ImageDecoration {
+    let stroke_width = obj.stroke_width.unwrap_or(0.0).max(0.0);
+    ImageDecoration {
+        corner_radius: obj.corner_radius.unwrap_or(0.0).max(0.0),
+        stroke: obj.stroke.as_deref().map(parse_hex_color).filter(|_| stroke_width > 0.0),
+        stroke_width,
+        shadow: object_shadow(obj),
+    }
 }
 
-// Helper: Overlay image onto canvas with position, size, rotation and opacity
+// Helper: Overlay image stretched to the layer frame, with rotation and opacity
 fn overlay_image(
     canvas: &mut RgbaImage,
     source: &DynamicImage,
-    x: i64,
-    y: i64,
-    width: u32,
-    height: u32,
-    rotation: f64,
+    frame: &LayerFrame,
+    decoration: &ImageDecoration,
     opacity: f64,
 ) {
-    // Resize source image to target dimensions
-    let resized = source.resize_exact(width, height, image::imageops::FilterType::Lanczos3);
-    let resized_rgba = resized.to_rgba8();
-
-    // Apply rotation if needed and calculate top-left position
-    let (final_img, top_left_x, top_left_y) = if rotation != 0.0 {
-        let rotated = rotate_image(&resized_rgba, rotation);
-        // Center should be at (x + width/2, y + height/2)
-        // Position rotated image so its center aligns with target center
-        let center_x = x + width as i64 / 2;
-        let center_y = y + height as i64 / 2;
-        let tlx = center_x - rotated.width() as i64 / 2;
-        let tly = center_y - rotated.height() as i64 / 2;
-        (rotated, tlx, tly)
-    } else {
-        (resized_rgba, x, y)
-    };
-
-    let (canvas_width, canvas_height) = canvas.dimensions();
-    let alpha_mult = (opacity * 255.0) as u8;
-
-    for (px, py, pixel) in final_img.enumerate_pixels() {
-        let target_x = top_left_x + px as i64;
-        let target_y = top_left_y + py as i64;
-
-        if target_x >= 0
-            && target_y >= 0
-            && target_x < canvas_width as i64
-            && target_y < canvas_height as i64
-        {
-            let tx = target_x as u32;
-            let ty = target_y as u32;
-
-            // Alpha blending
-            let src_alpha = ((pixel[3] as u32 * alpha_mult as u32) / 255) as u8;
-            if src_alpha > 0 {
-                let dst = canvas.get_pixel(tx, ty);
-                let inv_alpha = 255 - src_alpha;
-
-                let r = ((pixel[0] as u32 * src_alpha as u32
-                    + dst[0] as u32 * inv_alpha as u32)
-                    / 255) as u8;
-                let g = ((pixel[1] as u32 * src_alpha as u32
-                    + dst[1] as u32 * inv_alpha as u32)
-                    / 255) as u8;
-                let b = ((pixel[2] as u32 * src_alpha as u32
-                    + dst[2] as u32 * inv_alpha as u32)
-                    / 255) as u8;
-                let a = src_alpha.max(dst[3]);
-
-                canvas.put_pixel(tx, ty, Rgba([r, g, b, a]));
-            }
-        }
-    }
+    let rect = LocalRect::new(0.0, 0.0, frame.width, frame.height);
+    draw_image_layer(canvas, source, frame, rect, decoration, opacity);
 }
 
-// Helper: Overlay image with fit mode (preserves aspect ratio, rotation around bounding box center)
-fn overlay_image_fit(
+// Helper: Overlay image fitted into the layer frame (cover overflow is clipped to the frame)
+fn overlay_image_placed(
     canvas: &mut RgbaImage,
     source: &DynamicImage,
-    x: i64,
-    y: i64,
-    target_width: u32,
-    target_height: u32,
-    rotation: f64,
+    frame: &LayerFrame,
+    placement: &Placement,
+    decoration: &ImageDecoration,
     opacity: f64,
 ) {
-    let img_ratio = source.width() as f64 / source.height() as f64;
-    let target_ratio = target_width as f64 / target_height as f64;
+    let rect = fit_rect(source.width() as f64, source.height() as f64, frame.width, frame.height, placement);
+    draw_image_layer(canvas, source, frame, rect, decoration, opacity);
+}
 
-    let (render_width, render_height) = if img_ratio > target_ratio {
-        // Image is wider - fit to width
-        (target_width, (target_width as f64 / img_ratio) as u32)
-    } else {
-        // Image is taller - fit to height
-        ((target_height as f64 * img_ratio) as u32, target_height)
+// Helper: Draw an image occupying `rect` of the layer frame (clipped to the frame), with its shadow and border.
+// The parts are grouped before the layer opacity is applied, so the shadow doesn't show through the image.
+fn draw_image_layer(
+    canvas: &mut RgbaImage,
+    source: &DynamicImage,
+    frame: &LayerFrame,
+    rect: LocalRect,
+    decoration: &ImageDecoration,
+    opacity: f64,
+) {
+    let clip = LocalRect::new(0.0, 0.0, frame.width, frame.height);
+    let canvas_size = canvas.dimensions();
+    let Some(patch) = render_image_clipped(&as_rgba8(source), frame, rect, clip, decoration.corner_radius, canvas_size) else {
+        return;
     };
+    if decoration.shadow.is_none() && decoration.stroke.is_none() {
+        draw_patch(canvas, &patch, opacity);
+        return;
+    }
 
-    // Resize preserving aspect ratio
-    let resized = source.resize(render_width, render_height, image::imageops::FilterType::Lanczos3);
-    let resized_rgba = resized.to_rgba8();
-
-    // Calculate final image and position
-    let (final_img, top_left_x, top_left_y) = if rotation != 0.0 {
-        // Create bounding box sized buffer (matches editor behavior)
-        // In editor, rotation happens around bounding box center, not fitted image center
-        let mut bbox_buffer: RgbaImage = ImageBuffer::from_pixel(
-            target_width, target_height, Rgba([0, 0, 0, 0])
-        );
-
-        // Place fitted image at (0, 0) within bounding box (matches editor)
-        for (px, py, pixel) in resized_rgba.enumerate_pixels() {
-            if px < target_width && py < target_height {
-                bbox_buffer.put_pixel(px, py, *pixel);
+    let mut group = match &decoration.shadow {
+        Some(shadow) => shadow.cast_patch(&patch),
+        None => Patch {
+            x: patch.x,
+            y: patch.y,
+            pixels: RgbaImage::new(patch.pixels.width(), patch.pixels.height()),
+        },
+    };
+    merge_patch(&mut group, &patch);
+
+    if let Some(stroke) = decoration.stroke {
+        // Inside border: the shape stroke is centered on its outline, so the outline is inset by half the width
+        let visible = rect.intersect(&clip);
+        let inset = decoration.stroke_width / 2.0;
+        let (width, height) = (visible.width - 2.0 * inset, visible.height - 2.0 * inset);
+        let (center_x, center_y) = frame.to_canvas(visible.x + visible.width / 2.0, visible.y + visible.height / 2.0);
+        let style = ShapeStyle {
+            kind: ShapeKind::Rect,
+            fill: None,
+            fill_gradient: None,
+            stroke: Some(stroke),
+            stroke_width: decoration.stroke_width,
+            corner_radius: (decoration.corner_radius - inset).max(0.0),
+        };
+        let border_frame = LayerFrame::new(center_x - width / 2.0, center_y - height / 2.0, width, height, frame.rotation);
+        if width > 0.0 && height > 0.0 {
+            if let Some(border) = render_shape(&style, &border_frame, canvas_size) {
+                merge_patch(&mut group, &border);
             }
         }
+    }
 
-        // Rotate bounding box around its center
-        let rotated = rotate_image(&bbox_buffer, rotation);
-
-        // Position rotated image so center aligns with target center
-        let center_x = x + target_width as i64 / 2;
-        let center_y = y + target_height as i64 / 2;
-        let tlx = center_x - rotated.width() as i64 / 2;
-        let tly = center_y - rotated.height() as i64 / 2;
-        (rotated, tlx, tly)
-    } else {
-        (resized_rgba, x, y)
-    };
+    draw_patch(canvas, &group, opacity);
+}
 
-    let (canvas_width, canvas_height) = canvas.dimensions();
-    let alpha_mult = (opacity * 255.0) as u8;
-
-    for (px, py, pixel) in final_img.enumerate_pixels() {
-        let target_x = top_left_x + px as i64;
-        let target_y = top_left_y + py as i64;
-
-        if target_x >= 0
-            && target_y >= 0
-            && target_x < canvas_width as i64
-            && target_y < canvas_height as i64
-        {
-            let tx = target_x as u32;
-            let ty = target_y as u32;
-
-            // Alpha blending
-            let src_alpha = ((pixel[3] as u32 * alpha_mult as u32) / 255) as u8;
-            if src_alpha > 0 {
-                let dst = canvas.get_pixel(tx, ty);
-                let inv_alpha = 255 - src_alpha;
-
-                let r = ((pixel[0] as u32 * src_alpha as u32
-                    + dst[0] as u32 * inv_alpha as u32)
-                    / 255) as u8;
-                let g = ((pixel[1] as u32 * src_alpha as u32
-                    + dst[1] as u32 * inv_alpha as u32)
-                    / 255) as u8;
-                let b = ((pixel[2] as u32 * src_alpha as u32
-                    + dst[2] as u32 * inv_alpha as u32)
-                    / 255) as u8;
-                let a = src_alpha.max(dst[3]);
-
-                canvas.put_pixel(tx, ty, Rgba([r, g, b, a]));
-            }
-        }
+// Helper: Placement of the hero image, with the focal point from the image's table row
+fn hero_placement(obj: &CanvasObject, focus: Option<(f64, f64)>) -> Placement {
+    Placement {
+        fit: ImageFit::parse(obj.scale_mode.as_deref()),
+        align_x: match obj.align.as_deref() {
+            Some("left") => 0.0,
+            Some("right") => 1.0,
+            _ => 0.5,
+        },
+        align_y: match obj.vertical_align.as_deref() {
+            Some("top") => 0.0,
+            Some("bottom") => 1.0,
+            _ => 0.5,
+        },
+        focus,
     }
 }
 
-// Load system font
-fn load_system_font(family_name: &str) -> Option<Vec<u8>> {
-    use font_kit::family_name::FamilyName;
-    use font_kit::properties::Properties;
+// Helper: Size the hero is decoded at, matching how it fills its box
+fn hero_target(obj: &CanvasObject) -> TargetSize {
+    match ImageFit::parse(obj.scale_mode.as_deref()) {
+        ImageFit::Contain => TargetSize::Contain(obj.width, obj.height),
+        ImageFit::Cover => TargetSize::Cover(obj.width, obj.height),
+        ImageFit::Stretch => TargetSize::Stretch(obj.width, obj.height),
+    }
+}
 
-    let source = SystemSource::new();
+// Helper: Key out the background of an image layer if enabled (`drawn_width` = layer pixels the image spans)
+fn apply_keying(image: DynamicImage, obj: &CanvasObject, drawn_width: f64) -> DynamicImage {
+    if obj.key_background != Some(true) || image.width() == 0 {
+        return image;
+    }
+    let keying = Keying {
+        tolerance: obj.key_tolerance.unwrap_or(8.0).clamp(0.0, 100.0) / 100.0,
+        feather: obj.key_feather.unwrap_or(1.0).max(0.0) * image.width() as f64 / drawn_width.max(1.0),
+    };
+    let mut rgba = image.into_rgba8();
+    key_out_background(&mut rgba, &keying);
+    DynamicImage::ImageRgba8(rgba)
+}
 
-    // Try to find the requested font family
-    let family = if family_name.is_empty() {
-        FamilyName::SansSerif
-    } else {
-        FamilyName::Title(family_name.to_string())
+// Helper: Apply the layer's adjustments list, if any (`drawn_width` = layer pixels the image spans)
+fn apply_layer_adjustments(image: DynamicImage, obj: &CanvasObject, drawn_width: f64) -> DynamicImage {
+    let Some(adjustments) = obj.adjustments.as_deref().filter(|list| !list.is_empty()) else {
+        return image;
     };
+    let scale = image.width() as f64 / drawn_width.max(1.0);
+    let mut rgba = image.into_rgba8();
+    apply_adjustments(&mut rgba, adjustments, scale);
+    DynamicImage::ImageRgba8(rgba)
+}
 
-    if let Ok(handle) = source.select_best_match(&[family, FamilyName::SansSerif], &Properties::new()) {
-        if let Ok(font) = handle.load() {
-            if let Some(data) = font.copy_font_data() {
-                return Some(data.to_vec());
-            }
-        }
+// Helper: Size the hero is decoded at (with headroom when it is trimmed afterwards)
+fn hero_decode_target(obj: &CanvasObject) -> TargetSize {
+    match obj.auto_trim {
+        Some(true) => hero_target(obj).scaled(TRIM_DECODE_HEADROOM),
+        _ => hero_target(obj),
     }
+}
 
-    None
+// Helper: Extra working memory per decoded pixel of a layer's preprocessing (keying, trim, adjustments)
+fn preprocess_bytes_per_pixel(obj: &CanvasObject) -> u64 {
+    let keying = if obj.key_background == Some(true) { KEYING_BYTES_PER_PIXEL } else { 0 };
+    let trim = if obj.auto_trim == Some(true) { TRIM_BYTES_PER_PIXEL } else { 0 };
+    let adjust = if obj.adjustments.as_ref().is_some_and(|list| !list.is_empty()) { ADJUSTMENTS_BYTES_PER_PIXEL } else { 0 };
+    keying + trim + adjust
 }
 
-// Helper: Measure text width at given font size
-fn measure_text_width(font: &FontVec, text: &str, font_size: f32) -> f32 {
-    let scaled_font = font.as_scaled(PxScale::from(font_size));
-    text.chars()
-        .map(|c| {
-            let glyph_id = scaled_font.glyph_id(c);
-            scaled_font.h_advance(glyph_id)
+// Helper: Largest decoded size of the template's background, image and mask assets.
+// These are decoded at the file's own size (only JPEGs are reduced), one layer at a time.
+fn template_layer_bytes(objects: &[CanvasObject]) -> u64 {
+    objects
+        .iter()
+        .flat_map(|obj| {
+            let target = TargetSize::Stretch(obj.width, obj.height);
+            let source = match obj.obj_type.as_str() {
+                "background" | "image" => obj.original_path.as_deref(),
+                _ => None,
+            };
+            let mask = obj.mask.as_ref().filter(|mask| mask.kind != "shape").and_then(|mask| mask.src.as_deref());
+            [
+                source.map(|src| layer_bytes(Path::new(src), target, preprocess_bytes_per_pixel(obj))),
+                mask.map(|src| layer_bytes(Path::new(src), target, 0)),
+            ]
         })
-        .sum()
+        .flatten()
+        .max()
+        .unwrap_or(0)
+}
+
+// Helper: Trim the hero's margins if enabled, moving the focal point along with the crop
+fn apply_trim(
+    image: DynamicImage,
+    obj: &CanvasObject,
+    focus: Option<(f64, f64)>,
+) -> (DynamicImage, Option<(f64, f64)>) {
+    if obj.auto_trim != Some(true) {
+        return (image, focus);
+    }
+    let tolerance = obj.key_tolerance.unwrap_or(8.0).clamp(0.0, 100.0) / 100.0;
+    let padding = obj.trim_padding.unwrap_or(0.0).clamp(0.0, 100.0) / 100.0;
+    let Some(trimmed) = trim_to_content(&as_rgba8(&image), tolerance, padding) else {
+        return (image, focus);
+    };
+
+    let (width, height) = (image.width() as f64, image.height() as f64);
+    let (new_width, new_height) = (trimmed.image.width() as f64, trimmed.image.height() as f64);
+    let focus = focus.map(|(x, y)| {
+        (
+            ((x * width - trimmed.origin.0 as f64) / new_width).clamp(0.0, 1.0),
+            ((y * height - trimmed.origin.1 as f64) / new_height).clamp(0.0, 1.0),
+        )
+    });
+    (DynamicImage::ImageRgba8(trimmed.image), focus)
 }
 
-// Helper: Find font size that fits within box width
-fn fit_text_to_width(font: &FontVec, text: &str, max_font_size: f32, box_width: f32, min_font_size: f32) -> f32 {
-    let mut font_size = max_font_size;
+// Helper: Focal point of an image (0..1 per axis) from its table row, e.g. "30, 65"
+fn focal_point(image_path: &str, table_data: &HashMap<String, HashMap<String, String>>) -> Option<(f64, f64)> {
+    let value = table_data.get(image_path)?.get(FOCAL_POINT_KEY)?;
+    let (x, y) = value.split_once(',')?;
+    let x: f64 = x.trim().trim_end_matches('%').parse().ok().filter(|x: &f64| x.is_finite())?;
+    let y: f64 = y.trim().trim_end_matches('%').parse().ok().filter(|y: &f64| y.is_finite())?;
+    Some(((x / 100.0).clamp(0.0, 1.0), (y / 100.0).clamp(0.0, 1.0)))
+}
 
-    while font_size > min_font_size {
-        let text_width = measure_text_width(font, text, font_size);
-        if text_width <= box_width {
-            return font_size;
-        }
-        font_size -= 1.0;
+// Helper: Drop shadow of a layer, if a shadow color is set
+fn object_shadow(obj: &CanvasObject) -> Option<Shadow> {
+    let mut color = parse_hex_color(obj.shadow_color.as_deref()?);
+    if let Some(opacity) = obj.shadow_opacity {
+        color[3] = (color[3] as f64 * opacity.clamp(0.0, 1.0)).round() as u8;
     }
 
-    min_font_size
+    Some(Shadow {
+        color,
+        offset_x: obj.shadow_offset_x.unwrap_or(0.0),
+        offset_y: obj.shadow_offset_y.unwrap_or(0.0),
+        blur: obj.shadow_blur.unwrap_or(0.0).max(0.0),
+    })
 }
 
-// Helper: Draw rotated text onto canvas with auto-fit
-fn draw_rotated_text(
-    canvas: &mut RgbaImage,
-    text: &str,
-    x: i64,
-    y: i64,
-    width: u32,
-    height: u32,
-    rotation: f64,
-    font_size: f32,
-    color: Rgba<u8>,
-    font: &FontVec,
-    opacity: f64,
-) {
-    // Create a temporary buffer for the text
-    let mut text_buffer: RgbaImage = ImageBuffer::from_pixel(width, height, Rgba([0, 0, 0, 0]));
-
-    // Auto-fit font size to box width (minimum 8px)
-    let min_font_size = 8.0;
-    let fitted_font_size = fit_text_to_width(font, text, font_size, width as f32, min_font_size);
-
-    let scale = PxScale::from(fitted_font_size);
-    draw_text_mut(&mut text_buffer, color, 0, 0, scale, font, text);
-
-    // Apply rotation if needed and calculate top-left position
-    let (final_img, top_left_x, top_left_y) = if rotation != 0.0 {
-        let rotated = rotate_image(&text_buffer, rotation);
-        // Center should be at (x + width/2, y + height/2)
-        let center_x = x + width as i64 / 2;
-        let center_y = y + height as i64 / 2;
-        let tlx = center_x - rotated.width() as i64 / 2;
-        let tly = center_y - rotated.height() as i64 / 2;
-        (rotated, tlx, tly)
+// Helper: Text style of a text layer
+fn text_style(obj: &CanvasObject) -> TextStyle {
+    TextStyle {
+        font_size: obj.font_size.unwrap_or(32) as f32,
+        fill: obj
+            .fill
+            .as_deref()
+            .map(parse_hex_color)
+            .unwrap_or(Rgba([0, 0, 0, 255])),
+        fill_gradient: obj
+            .fill_gradient
+            .as_ref()
+            .map(|gradient| GradientFill::new(gradient, obj.width, obj.height, parse_hex_color)),
+        stroke: obj.stroke.as_deref().map(parse_hex_color),
+        stroke_width: obj.stroke_width.unwrap_or(0.0),
+        shadow: object_shadow(obj),
+        background: obj.background_color.as_deref().map(|color| TextBackground {
+            color: parse_hex_color(color),
+            padding: obj.background_padding.unwrap_or(0.0),
+            corner_radius: obj.corner_radius,
+        }),
+    }
+}
+
+// Helper: Font family and weight of a text layer (empty family = default sans-serif)
+fn text_font(obj: &CanvasObject) -> (&str, bool) {
+    let family = obj.font_family.as_deref().unwrap_or("").trim();
+    let bold = matches!(obj.font_weight.as_deref(), Some("bold" | "600" | "700" | "800" | "900"));
+    (family, bold)
+}
+
+// Helper: Styled runs of a text layer's content (markup is only parsed when the layer opts in)
+fn text_runs(obj: &CanvasObject, content: &str) -> Vec<TextRun> {
+    if obj.markup.unwrap_or(false) {
+        parse_markup(content)
     } else {
-        (text_buffer, x, y)
-    };
+        plain_text(content)
+    }
+}
 
-    // Overlay the text buffer onto the canvas
-    let (canvas_width, canvas_height) = canvas.dimensions();
-    let alpha_mult = (opacity * 255.0) as u8;
-
-    for (px, py, pixel) in final_img.enumerate_pixels() {
-        let target_x = top_left_x + px as i64;
-        let target_y = top_left_y + py as i64;
-
-        if target_x >= 0
-            && target_y >= 0
-            && target_x < canvas_width as i64
-            && target_y < canvas_height as i64
-        {
-            let tx = target_x as u32;
-            let ty = target_y as u32;
-
-            // Alpha blending
-            let src_alpha = ((pixel[3] as u32 * alpha_mult as u32) / 255) as u8;
-            if src_alpha > 0 {
-                let dst = canvas.get_pixel(tx, ty);
-                let inv_alpha = 255 - src_alpha;
-
-                let r = ((pixel[0] as u32 * src_alpha as u32
-                    + dst[0] as u32 * inv_alpha as u32)
-                    / 255) as u8;
-                let g = ((pixel[1] as u32 * src_alpha as u32
-                    + dst[1] as u32 * inv_alpha as u32)
-                    / 255) as u8;
-                let b = ((pixel[2] as u32 * src_alpha as u32
-                    + dst[2] as u32 * inv_alpha as u32)
-                    / 255) as u8;
-                let a = src_alpha.max(dst[3]);
-
-                canvas.put_pixel(tx, ty, Rgba([r, g, b, a]));
+// Helper: Text of a text layer for an image (table value, falling back to the layer content)
+fn text_content<'a>(
+    obj: &'a CanvasObject,
+    image_path: &str,
+    table_data: &'a HashMap<String, HashMap<String, String>>,
+) -> Option<&'a str> {
+    let key = obj.key.as_ref()?;
+    table_data
+        .get(image_path)
+        .and_then(|row| row.get(key))
+        .map(|s| s.as_str())
+        .or(obj.content.as_deref())
+        .filter(|s| !s.is_empty())
+}
+
+// Helper: Characters each layer font has to render across the batch
+fn font_requirements(
+    objects: &[CanvasObject],
+    images: &[ImageFileInfo],
+    table_data: &HashMap<String, HashMap<String, String>>,
+) -> HashMap<FontKey, BTreeSet<char>> {
+    let mut requirements: HashMap<FontKey, BTreeSet<char>> = HashMap::new();
+
+    for obj in objects.iter().filter(|o| o.obj_type == "text") {
+        let (family, layer_bold) = text_font(obj);
+        for image in images {
+            let Some(content) = text_content(obj, &image.path, table_data) else {
+                continue;
+            };
+            for TextRun { text, bold, .. } in text_runs(obj, content) {
+                let key = FontKey {
+                    family: family.to_string(),
+                    bold: bold || layer_bold,
+                };
+                requirements.entry(key).or_default().extend(text.chars());
             }
         }
     }
+
+    requirements
+}
+
+// Helper: Output file of an image relative to the output directory.
+// Images from subfolders keep their folder structure in the output (avoids name collisions).
+fn output_relative_path(image_info: &ImageFileInfo) -> PathBuf {
+    let output_name = format!(
+        "{}_infographic.png",
+        Path::new(&image_info.path)
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| "output".to_string())
+    );
+    let subfolder: PathBuf = Path::new(&image_info.relative_path)
+        .parent()
+        .map(|parent| {
+            parent
+                .components()
+                .filter(|c| matches!(c, std::path::Component::Normal(_)))
+                .collect()
+        })
+        .unwrap_or_default();
+    subfolder.join(output_name)
+}
+
+// Helper: Layers that are drawn, bottom to top.
+// A hidden clipping group base hides the layers clipped to it (as in Photoshop), so they never
+// clip to whatever lies below the group instead. The first layer is never clipped.
+fn drawn_objects(objects: Vec<CanvasObject>) -> Vec<CanvasObject> {
+    let mut base_visible = true;
+    objects
+        .into_iter()
+        .enumerate()
+        .filter(|(index, obj)| {
+            if obj.clip_to_below.unwrap_or(false) && *index > 0 {
+                obj.visible && base_visible
+            } else {
+                base_visible = obj.visible;
+                obj.visible
+            }
+        })
+        .map(|(_, obj)| obj)
+        .collect()
 }
 
 // Process a single image (runs in blocking thread)
@@ -623,124 +858,200 @@ fn process_single_image(
     frame: &FrameSettings,
     table_data: &HashMap<String, HashMap<String, String>>,
     output_path: &Path,
-    font_bytes: &[u8],
-) -> Result<String, String> {
-    // Load font for this thread
-    let font = FontVec::try_from_vec(font_bytes.to_vec())
-        .map_err(|_| "Failed to load font".to_string())?;
+    font_library: &FontLibrary,
+    load_options: LoadOptions,
+) -> Result<GeneratedImage, GenerationError> {
+    let mut warnings: Vec<String> = Vec::new();
+    let fail = |stage: ErrorStage, error: ClassifiedError| GenerationError::new(&image_info.path, &image_info.name, stage, error);
 
-    // Create canvas with background color
+    // Parse fonts for this thread
+    let fonts = LoadedFonts::load(font_library);
+
+    // Create canvas with background color (and gradient)
     let bg_color = parse_hex_color(&frame.background_color);
     let mut canvas: RgbaImage = ImageBuffer::from_pixel(frame.width, frame.height, bg_color);
+    if let Some(gradient) = &frame.background_gradient {
+        GradientFill::new(gradient, frame.width as f64, frame.height as f64, parse_hex_color).paint(&mut canvas);
+    }
 
-    // Load hero image
     let hero_path = Path::new(&image_info.path);
-    let hero_image = image::open(hero_path)
-        .map_err(|e| format!("Failed to load {}: {}", image_info.name, e))?;
+
+    // Base of the current clipping group: the last layer drawn that the next one clips to
+    let mut clip_base: Option<RgbaImage> = None;
 
     // Process objects in order (background first, then others)
-    for obj in visible_objects {
+    for (index, obj) in visible_objects.iter().enumerate() {
+        let clipped = obj.clip_to_below.unwrap_or(false) && index > 0;
+        let is_clip_base = !clipped && visible_objects.get(index + 1).is_some_and(|next| next.clip_to_below.unwrap_or(false));
+
+        // Masked layers and clipping groups are drawn into a layer of their own first
+        let mut layer = (obj.mask.is_some() || clipped || is_clip_base).then(|| RgbaImage::new(frame.width, frame.height));
+        let surface = layer.as_mut().unwrap_or(&mut canvas);
+
         match obj.obj_type.as_str() {
             "background" => {
                 if let Some(src) = &obj.original_path {
-                    if let Ok(bg_img) = image::open(Path::new(src)) {
-                        overlay_image(
-                            &mut canvas,
-                            &bg_img,
-                            obj.x as i64,
-                            obj.y as i64,
-                            obj.width as u32,
-                            obj.height as u32,
-                            obj.rotation,
-                            obj.opacity,
-                        );
+                    if let Ok(bg_img) = load_image(Path::new(src), load_options, TargetSize::Stretch(obj.width, obj.height)) {
+                        let bg_img = apply_layer_adjustments(bg_img, obj, obj.width);
+                        overlay_image(surface, &bg_img, &object_frame(obj), &ImageDecoration::default(), obj.opacity);
                     }
                 }
             }
             "hero" => {
-                overlay_image_fit(
-                    &mut canvas,
+                // Vector heroes are rasterized at the size they are drawn, so they never upscale
+                let target = hero_target(obj);
+                let hero_image = load_image(hero_path, load_options, hero_decode_target(obj))
+                    .map_err(|e| fail(ErrorStage::Decode, e))?;
+                let (drawn_width, _) = target.drawn_size(hero_image.width() as f64, hero_image.height() as f64);
+                let hero_image = apply_keying(hero_image, obj, drawn_width);
+                let (hero_image, focus) = apply_trim(hero_image, obj, focal_point(&image_info.path, table_data));
+
+                let (drawn_width, drawn_height) = target.drawn_size(hero_image.width() as f64, hero_image.height() as f64);
+                let scale = (drawn_width / hero_image.width() as f64).max(drawn_height / hero_image.height() as f64);
+                if scale > 1.0 && !is_svg(hero_path) {
+                    warnings.push(format!(
+                        "{}: {}×{} image is upscaled {:.1}× to fit the hero",
+                        image_info.name,
+                        hero_image.width(),
+                        hero_image.height(),
+                        scale
+                    ));
+                }
+                let hero_image = apply_layer_adjustments(hero_image, obj, drawn_width);
+                let placement = hero_placement(obj, focus);
+                overlay_image_placed(
+                    surface,
                     &hero_image,
-                    obj.x as i64,
-                    obj.y as i64,
-                    obj.width as u32,
-                    obj.height as u32,
-                    obj.rotation,
+                    &object_frame(obj),
+                    &placement,
+                    &image_decoration(obj),
                     obj.opacity,
                 );
             }
             "image" => {
                 if let Some(src) = &obj.original_path {
-                    if let Ok(img) = image::open(Path::new(src)) {
-                        overlay_image(
-                            &mut canvas,
-                            &img,
-                            obj.x as i64,
-                            obj.y as i64,
-                            obj.width as u32,
-                            obj.height as u32,
-                            obj.rotation,
-                            obj.opacity,
-                        );
+                    if let Ok(img) = load_image(Path::new(src), load_options, TargetSize::Stretch(obj.width, obj.height)) {
+                        let img = apply_layer_adjustments(apply_keying(img, obj, obj.width), obj, obj.width);
+                        overlay_image(surface, &img, &object_frame(obj), &image_decoration(obj), obj.opacity);
                     }
                 }
             }
+            "shape" => {
+                let gradient = obj
+                    .fill_gradient
+                    .as_ref()
+                    .map(|gradient| GradientFill::new(gradient, obj.width, obj.height, parse_hex_color));
+                let style = ShapeStyle {
+                    kind: ShapeKind::parse(obj.shape.as_deref()),
+                    fill: obj.fill.as_deref().map(parse_hex_color),
+                    fill_gradient: gradient.as_ref(),
+                    stroke: obj.stroke.as_deref().map(parse_hex_color),
+                    stroke_width: obj.stroke_width.unwrap_or(0.0),
+                    corner_radius: obj.corner_radius.unwrap_or(0.0),
+                };
+                if let Some(patch) = render_shape(&style, &object_frame(obj), surface.dimensions()) {
+                    draw_patch(surface, &patch, obj.opacity);
+                }
+            }
             "text" => {
-                if let Some(key) = &obj.key {
-                    let text_content = table_data
-                        .get(&image_info.path)
-                        .and_then(|row| row.get(key))
-                        .map(|s| s.as_str())
-                        .or(obj.content.as_deref())
-                        .unwrap_or("");
-
-                    if !text_content.is_empty() {
-                        let font_size = obj.font_size.unwrap_or(32) as f32;
-                        let color = obj
-                            .fill
-                            .as_ref()
-                            .map(|c| parse_hex_color(c))
-                            .unwrap_or(Rgba([0, 0, 0, 255]));
-
-                        draw_rotated_text(
-                            &mut canvas,
-                            text_content,
-                            obj.x as i64,
-                            obj.y as i64,
-                            obj.width as u32,
-                            obj.height as u32,
-                            obj.rotation,
-                            font_size,
-                            color,
-                            &font,
-                            obj.opacity,
-                        );
-                    }
+                if let Some(text_content) = text_content(obj, &image_info.path, table_data) {
+                    let (family, bold) = text_font(obj);
+                    draw_text_layer(
+                        surface,
+                        &text_runs(obj, text_content),
+                        &object_frame(obj),
+                        &text_style(obj),
+                        &fonts.for_layer(family, bold),
+                        obj.opacity,
+                    );
                 }
             }
             _ => {}
         }
-    }
 
-    // Save result
-    let output_name = format!(
-        "{}_infographic.png",
-        hero_path
-            .file_stem()
-            .map(|s| s.to_string_lossy().to_string())
-            .unwrap_or_else(|| "output".to_string())
-    );
-    let output_file = output_path.join(&output_name);
+        if let Some(mut layer) = layer {
+            if let Some(mask) = &obj.mask {
+                match render_mask(mask, obj, load_options, frame) {
+                    Some(mask_image) => apply_mask(&mut layer, &mask_image, MaskMode::parse(Some(&mask.kind)), mask.invert.unwrap_or(false)),
+                    None => warnings.push(format!("{}: mask of layer \"{}\" could not be loaded", image_info.name, obj.name)),
+                }
+            }
+            if clipped {
+                if let Some(base) = &clip_base {
+                    apply_mask(&mut layer, base, MaskMode::Alpha, false);
+                }
+            }
+            let patch = Patch { x: 0, y: 0, pixels: layer };
+            draw_patch(&mut canvas, &patch, 1.0);
+            if is_clip_base {
+                clip_base = Some(patch.pixels);
+            }
+        }
+    }
 
+    // Save result (encoded in memory first, so encode and write failures are told apart)
+    let output_file = output_path.join(output_relative_path(image_info));
+    let mut encoded: Vec<u8> = Vec::new();
     canvas
-        .save(&output_file)
-        .map_err(|e| format!("Failed to save {}: {}", output_name, e))?;
+        .write_to(&mut Cursor::new(&mut encoded), ImageFormat::Png)
+        .map_err(|e| fail(ErrorStage::Encode, e.into()))?;
+    if let Some(output_dir) = output_file.parent() {
+        fs::create_dir_all(output_dir).map_err(|e| fail(ErrorStage::Write, e.into()))?;
+    }
+    let bytes = encoded.len() as u64;
+    fs::write(&output_file, encoded).map_err(|e| fail(ErrorStage::Write, e.into()))?;
 
-    Ok(output_file.to_string_lossy().to_string())
+    Ok(GeneratedImage {
+        path: output_file.to_string_lossy().to_string(),
+        bytes,
+        warnings,
+    })
 }
 
 #[tauri::command]
 async fn generate_infographics(app: AppHandle, request: GenerateRequest) -> Result<GenerateResult, String> {
+    // The job is saved first so an interrupted batch can be resumed
+    let jobs_dir = Path::new(&request.working_directory).join(JOBS_DIR);
+    let job = Job::create(&jobs_dir, request)?;
+    let images = job.pending_images();
+    run_job(app, job, images).await
+}
+
+// Jobs that were interrupted (e.g. by a crash) or have failed images
+#[tauri::command]
+fn list_unfinished_jobs(working_directory: String) -> Vec<JobSummary> {
+    jobs::list_unfinished_jobs(&Path::new(&working_directory).join(JOBS_DIR))
+}
+
+// Continue an interrupted job with its original settings (only unprocessed images)
+#[tauri::command]
+async fn resume_job(app: AppHandle, working_directory: String, job_id: String) -> Result<GenerateResult, String> {
+    let job = Job::load(&Path::new(&working_directory).join(JOBS_DIR), &job_id)?;
+    let images = job.pending_images();
+    run_job(app, job, images).await
+}
+
+// Re-run only the failed images of a job with its original settings
+#[tauri::command]
+async fn retry_failed(app: AppHandle, working_directory: String, job_id: String) -> Result<GenerateResult, String> {
+    let job = Job::load(&Path::new(&working_directory).join(JOBS_DIR), &job_id)?;
+    let images = job.failed_images();
+    run_job(app, job, images).await
+}
+
+#[tauri::command]
+fn discard_job(working_directory: String, job_id: String) {
+    jobs::remove_job(&Path::new(&working_directory).join(JOBS_DIR), &job_id);
+}
+
+// Helper: Generate the given images of a job, logging each finished image
+async fn run_job(app: AppHandle, job: Job, images: Vec<ImageFileInfo>) -> Result<GenerateResult, String> {
+    let job_log = Arc::new(job.open_log()?);
+    let job_id = job.id.clone();
+    let summary = job.summary();
+    let left_over = (summary.pending + summary.failed).saturating_sub(images.len()); // Images this run does not touch
+    let request = job.request;
+
     // Create output directory (with template subdirectory if template is active)
     let output_path: PathBuf = if let Some(ref template_name) = request.template_name {
         Path::new(&request.working_directory)
@@ -751,25 +1062,18 @@ async fn generate_infographics(app: AppHandle, request: GenerateRequest) -> Resu
     };
     fs::create_dir_all(&output_path).map_err(|e| e.to_string())?;
 
-    // Load font bytes (will be cloned for each thread)
-    let font_bytes: Arc<Vec<u8>> = Arc::new(
-        load_system_font("Arial")
-            .or_else(|| load_system_font("Helvetica"))
-            .or_else(|| load_system_font(""))
-            .ok_or("No system fonts available")?,
-    );
-
     // Get visible objects only
-    let visible_objects: Arc<Vec<CanvasObject>> = Arc::new(
-        request
-            .objects
-            .into_iter()
-            .filter(|o| o.visible)
-            .collect(),
-    );
+    let visible_objects: Arc<Vec<CanvasObject>> = Arc::new(drawn_objects(request.objects));
+
+    // Resolve font fallback chains once for the whole batch (shared by all threads)
+    let requirements = font_requirements(&visible_objects, &images, &request.table_data);
+    let fallback_fonts = request.fallback_fonts.unwrap_or_default();
+    let (bundled_fonts, mut warnings) = load_bundled_fonts(&Path::new(&request.working_directory).join(FONTS_DIR));
+    let (font_library, font_warnings) = FontLibrary::resolve(&requirements, &fallback_fonts, &bundled_fonts)?;
+    warnings.extend(font_warnings);
+    let font_library = Arc::new(font_library);
 
-    let total = request.selected_images.len();
-    let counter = Arc::new(AtomicUsize::new(0));
+    let total = images.len();
     let frame = Arc::new(request.frame);
     let table_data = Arc::new(request.table_data);
     let output_path = Arc::new(output_path);
@@ -780,19 +1084,56 @@ async fn generate_infographics(app: AppHandle, request: GenerateRequest) -> Resu
     let default_parallelism = (max_cores / 2).max(1);
     let parallelism = request.parallelism.unwrap_or(default_parallelism).clamp(1, max_cores);
 
+    let load_options = LoadOptions {
+        apply_orientation: request.apply_exif_orientation.unwrap_or(true),
+    };
+    let progress = Arc::new(ProgressTracker::new(total, parallelism));
+
+    // Concurrency is also limited by memory: each image holds permits (MiB) for its estimated peak usage
+    let budget_mb = memory_budget_mb(request.memory_budget_mb).min(Semaphore::MAX_PERMITS as u64).min(u32::MAX as u64);
+    let memory = Arc::new(Semaphore::new(budget_mb as usize));
+    let hero = visible_objects.iter().find(|o| o.obj_type == "hero");
+    let hero_target = hero.map(hero_decode_target);
+    let hero_preprocess = hero.map_or(0, preprocess_bytes_per_pixel);
+    let frame_size = (frame.width, frame.height);
+
+    // Estimates read the headers of layer assets (and of images sent without metadata), so they run off the async runtime.
+    // An image larger than the whole budget runs alone.
+    let layer_objects = visible_objects.clone();
+    let images: Vec<(ImageFileInfo, u64)> = tokio::task::spawn_blocking(move || {
+        let layers = template_layer_bytes(&layer_objects);
+        images
+            .into_iter()
+            .map(|image| {
+                let needed_mb = estimate_memory_mb(&image, hero_target, hero_preprocess, layers, frame_size).min(budget_mb);
+                (image, needed_mb)
+            })
+            .collect()
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?;
+
     // Process images in parallel with limited concurrency
-    let results: Vec<Result<String, String>> = stream::iter(request.selected_images)
-        .map(|image_info| {
+    let results: Vec<Result<GeneratedImage, GenerationError>> = stream::iter(images)
+        .map(|(image_info, needed_mb)| {
             let app = app.clone();
-            let counter = counter.clone();
+            let progress = progress.clone();
             let frame = frame.clone();
             let visible_objects = visible_objects.clone();
             let table_data = table_data.clone();
             let output_path = output_path.clone();
-            let font_bytes = font_bytes.clone();
+            let font_library = font_library.clone();
+            let job_log = job_log.clone();
+            let memory = memory.clone();
             let image_name = image_info.name.clone();
+            let image_path = image_info.path.clone();
 
             async move {
+                let _permit = memory.acquire_many(needed_mb as u32).await;
+
+                let (worker, started) = progress.start(&image_name);
+                let _ = app.emit("generation-progress", started);
+
                 // Run CPU-intensive work in blocking thread
                 let result = tokio::task::spawn_blocking(move || {
                     process_single_image(
@@ -801,22 +1142,23 @@ async fn generate_infographics(app: AppHandle, request: GenerateRequest) -> Resu
                         &frame,
                         &table_data,
                         &output_path,
-                        &font_bytes,
+                        &font_library,
+                        load_options,
                     )
                 })
                 .await
-                .map_err(|e| format!("Task failed: {}", e))?;
-
-                // Update progress (atomic counter for thread safety)
-                let current = counter.fetch_add(1, Ordering::SeqCst) + 1;
-                let _ = app.emit(
-                    "generation-progress",
-                    GenerationProgress {
-                        current,
-                        total,
-                        current_file: image_name,
-                    },
-                );
+                .unwrap_or_else(|e| {
+                    let error = ClassifiedError::new(ErrorKind::Internal, format!("Task failed: {}", e));
+                    Err(GenerationError::new(&image_path, &image_name, ErrorStage::Render, error))
+                });
+
+                match &result {
+                    Ok(_) => job_log.record(&image_path, ImageStatus::Done, None),
+                    Err(e) => job_log.record(&image_path, ImageStatus::Failed, Some(e)),
+                }
+
+                let bytes = result.as_ref().ok().map(|generated| generated.bytes);
+                let _ = app.emit("generation-progress", progress.finish(worker, &image_name, bytes));
 
                 result
             }
@@ -827,19 +1169,31 @@ async fn generate_infographics(app: AppHandle, request: GenerateRequest) -> Resu
 
     // Collect results
     let mut generated_files: Vec<String> = Vec::new();
-    let mut errors: Vec<String> = Vec::new();
+    let mut errors: Vec<GenerationError> = Vec::new();
 
     for result in results {
         match result {
-            Ok(path) => generated_files.push(path),
+            Ok(generated) => {
+                generated_files.push(generated.path);
+                warnings.extend(generated.warnings);
+            }
             Err(e) => errors.push(e),
         }
     }
 
+    // The job is kept while it has failed images that can be retried
+    if errors.is_empty() && left_over == 0 {
+        if let Ok(job_log) = Arc::try_unwrap(job_log) {
+            job_log.remove();
+        }
+    }
+
     Ok(GenerateResult {
         success: errors.is_empty(),
+        job_id,
         generated_files,
         errors,
+        warnings,
     })
 }
 
@@ -849,16 +1203,112 @@ pub fn run() {
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
+        .manage(WatcherState::default())
         .invoke_handler(tauri::generate_handler![
             get_cpu_info,
             get_system_fonts,
             list_images_in_directory,
+            get_thumbnail,
+            prefetch_thumbnails,
+            watch_images,
+            unwatch_images,
             save_template,
             load_template,
             list_templates,
             delete_template,
-            generate_infographics
+            generate_infographics,
+            list_unfinished_jobs,
+            resume_job,
+            retry_failed,
+            discard_job
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(id: &str, x: f64, width: f64, fill: &str, visible: bool, clip_to_below: bool) -> CanvasObject {
+        serde_json::from_value(serde_json::json!({
+            "id": id, "type": "shape", "name": id, "shape": "rect", "fill": fill,
+            "x": x, "y": 0.0, "width": width, "height": 20.0, "rotation": 0.0, "opacity": 1.0,
+            "visible": visible, "clipToBelow": clip_to_below,
+        }))
+        .unwrap()
+    }
+
+    // Helper: Render the layers onto a white 40×20 canvas; returns the left and right half colors
+    fn render(name: &str, objects: Vec<CanvasObject>) -> (Rgba<u8>, Rgba<u8>) {
+        let (bundled, _) = load_bundled_fonts(&Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures"));
+        let (library, _) = FontLibrary::resolve(&HashMap::new(), &[], &bundled).expect("fonts resolve");
+        let output = std::env::temp_dir().join(format!("infographics-render-{}-{}", std::process::id(), name));
+        let image = ImageFileInfo {
+            path: output.join("source.png").to_string_lossy().to_string(),
+            name: "source.png".to_string(),
+            relative_path: "source.png".to_string(),
+            metadata: ImageMetadata::default(),
+        };
+        let frame = FrameSettings {
+            width: 40,
+            height: 20,
+            background_color: "#ffffff".to_string(),
+            background_gradient: None,
+        };
+        let options = LoadOptions { apply_orientation: true };
+        let generated = process_single_image(&image, &drawn_objects(objects), &frame, &HashMap::new(), &output, &library, options)
+            .unwrap_or_else(|e| panic!("{:?}", e.message));
+        let canvas = image::open(&generated.path).unwrap().to_rgba8();
+        let _ = fs::remove_dir_all(&output);
+        (*canvas.get_pixel(10, 10), *canvas.get_pixel(30, 10))
+    }
+
+    const WHITE: Rgba<u8> = Rgba([255, 255, 255, 255]);
+    const GREEN: Rgba<u8> = Rgba([0, 255, 0, 255]);
+    const BLUE: Rgba<u8> = Rgba([0, 0, 255, 255]);
+
+    fn focus(value: &str) -> Option<(f64, f64)> {
+        let table = HashMap::from([("a.png".to_string(), HashMap::from([(FOCAL_POINT_KEY.to_string(), value.to_string())]))]);
+        focal_point("a.png", &table)
+    }
+
+    #[test]
+    fn focal_point_reads_percentages() {
+        assert_eq!(focus("25,75"), Some((0.25, 0.75)));
+        assert_eq!(focus(" 10.5% , 50% "), Some((0.105, 0.5)));
+        assert_eq!(focus("-20,150"), Some((0.0, 1.0)));
+        for invalid in ["", "50", "50,", "1,2,3", "x,50", "NaN,50", "inf,50"] {
+            assert_eq!(focus(invalid), None, "{:?}", invalid);
+        }
+    }
+
+    #[test]
+    fn clipped_layers_show_only_over_their_base() {
+        let objects = vec![
+            rect("base", 0.0, 20.0, "#ff0000", true, false),
+            rect("clipped", 0.0, 40.0, "#0000ff", true, true),
+        ];
+        assert_eq!(render("clip", objects), (BLUE, WHITE));
+    }
+
+    #[test]
+    fn hidden_base_hides_its_clipping_group() {
+        let objects = vec![
+            rect("below", 20.0, 20.0, "#00ff00", true, false),
+            rect("base", 0.0, 20.0, "#ff0000", false, false),
+            rect("clipped", 0.0, 40.0, "#0000ff", true, true),
+        ];
+        assert_eq!(render("hidden-base", objects), (WHITE, GREEN));
+    }
+
+    #[test]
+    fn hidden_clipped_layer_keeps_the_rest_of_its_group() {
+        let objects = vec![
+            rect("base", 0.0, 20.0, "#ff0000", true, false),
+            rect("hidden", 0.0, 40.0, "#0000ff", false, true),
+            rect("clipped", 0.0, 40.0, "#00ff00", true, true),
+        ];
+        assert_eq!(render("hidden-clipped", objects), (GREEN, WHITE));
+    }
+}
@@ -0,0 +1,191 @@
+use crate::errors::{ClassifiedError, ErrorKind};
+use image::{DynamicImage, GrayImage, ImageDecoder, ImageFormat, ImageReader, RgbImage, RgbaImage};
+use resvg::{tiny_skia, usvg};
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
+
+const MAX_SVG_SIZE: f32 = 16384.0; // Rasterized SVG dimensions are capped to this many pixels
+const REDUCED_DECODE_MARGIN: f64 = 2.0; // Reduced JPEG decodes keep at least 2 source pixels per drawn pixel
+
+// How the renderer loads source images
+#[derive(Debug, Clone, Copy)]
+pub struct LoadOptions {
+    pub apply_orientation: bool, // Rotate/flip according to the EXIF orientation tag
+}
+
+// Size a layer draws its image at (canvas pixels); vector images are rasterized directly to it
+#[derive(Debug, Clone, Copy)]
+pub enum TargetSize {
+    Stretch(f64, f64), // Fills the box, aspect ratio is not kept
+    Contain(f64, f64), // Fits inside the box, aspect ratio is kept
+    Cover(f64, f64),   // Fills the box, aspect ratio is kept (overflow is cropped)
+}
+
+impl TargetSize {
+    // Same box with width and height swapped (for sources stored rotated by 90 degrees)
+    fn transposed(self) -> TargetSize {
+        match self {
+            TargetSize::Stretch(width, height) => TargetSize::Stretch(height, width),
+            TargetSize::Contain(width, height) => TargetSize::Contain(height, width),
+            TargetSize::Cover(width, height) => TargetSize::Cover(height, width),
+        }
+    }
+
+    // Same kind of box, `factor` times larger
+    pub fn scaled(self, factor: f64) -> TargetSize {
+        match self {
+            TargetSize::Stretch(width, height) => TargetSize::Stretch(width * factor, height * factor),
+            TargetSize::Contain(width, height) => TargetSize::Contain(width * factor, height * factor),
+            TargetSize::Cover(width, height) => TargetSize::Cover(width * factor, height * factor),
+        }
+    }
+
+    // Drawn size of a `width`×`height` source
+    pub fn drawn_size(self, width: f64, height: f64) -> (f64, f64) {
+        match self {
+            TargetSize::Stretch(w, h) => (w, h),
+            TargetSize::Contain(w, h) => {
+                let scale = (w / width).min(h / height);
+                (width * scale, height * scale)
+            }
+            TargetSize::Cover(w, h) => {
+                let scale = (w / width).max(h / height);
+                (width * scale, height * scale)
+            }
+        }
+    }
+}
+
+pub fn is_svg(path: &Path) -> bool {
+    path.extension()
+        .map(|ext| matches!(ext.to_string_lossy().to_lowercase().as_str(), "svg" | "svgz"))
+        .unwrap_or(false)
+}
+
+// Load an image for rendering
+pub fn load_image(path: &Path, options: LoadOptions, target: TargetSize) -> Result<DynamicImage, ClassifiedError> {
+    if is_svg(path) {
+        return rasterize_svg(path, target).map(DynamicImage::ImageRgba8);
+    }
+
+    let reader = ImageReader::open(path)?.with_guessed_format()?;
+    let is_jpeg = reader.format() == Some(ImageFormat::Jpeg);
+    let mut decoder = reader.into_decoder()?;
+
+    // Orientation has to be read before the decoder is consumed
+    let orientation = if options.apply_orientation {
+        decoder.orientation().ok()
+    } else {
+        None
+    };
+
+    // JPEGs far larger than the layer are decoded at 1/2, 1/4 or 1/8 scale (DCT domain, much less memory)
+    let reduced = if is_jpeg {
+        let (width, height) = decoder.dimensions();
+        let rotated = orientation.is_some_and(|o| o.to_exif() >= 5);
+        let target = if rotated { target.transposed() } else { target };
+        match jpeg_scale_denominator(width, height, target) {
+            1 => None,
+            denominator => decode_jpeg_scaled(path, denominator),
+        }
+    } else {
+        None
+    };
+
+    // Anything the reduced path cannot handle (CMYK, 16-bit, errors) takes the regular decoder
+    let mut image = match reduced {
+        Some(image) => image,
+        None => DynamicImage::from_decoder(decoder)?,
+    };
+    if let Some(orientation) = orientation {
+        image.apply_orientation(orientation);
+    }
+    Ok(image)
+}
+
+// Largest JPEG scale denominator (1, 2, 4 or 8) that still leaves the margin for the drawn size
+pub fn jpeg_scale_denominator(width: u32, height: u32, target: TargetSize) -> u32 {
+    if width == 0 || height == 0 {
+        return 1;
+    }
+    // Keep detail along the axis that is shrunk least (stretched images scale each axis differently)
+    let (drawn_width, drawn_height) = target.drawn_size(width as f64, height as f64);
+    let drawn_scale = (drawn_width / width as f64).max(drawn_height / height as f64);
+    [8, 4, 2]
+        .into_iter()
+        .find(|&denominator| 1.0 / denominator as f64 >= drawn_scale * REDUCED_DECODE_MARGIN)
+        .unwrap_or(1)
+}
+
+// Helper: Decode a JPEG at 1/denominator scale; None when the reduced path does not apply
+fn decode_jpeg_scaled(path: &Path, denominator: u32) -> Option<DynamicImage> {
+    let mut decoder = jpeg_decoder::Decoder::new(BufReader::new(File::open(path).ok()?));
+    decoder.read_info().ok()?;
+    let info = decoder.info()?;
+    let (width, height) = (info.width as u32, info.height as u32);
+    decoder
+        .scale(width.div_ceil(denominator) as u16, height.div_ceil(denominator) as u16)
+        .ok()?;
+
+    let pixels = decoder.decode().ok()?;
+    let info = decoder.info()?;
+    let (width, height) = (info.width as u32, info.height as u32);
+    match info.pixel_format {
+        jpeg_decoder::PixelFormat::RGB24 => RgbImage::from_raw(width, height, pixels).map(DynamicImage::ImageRgb8),
+        jpeg_decoder::PixelFormat::L8 => GrayImage::from_raw(width, height, pixels).map(DynamicImage::ImageLuma8),
+        _ => None,
+    }
+}
+
+// Intrinsic size of an SVG document (without loading fonts)
+pub fn svg_size(path: &Path) -> Option<(f32, f32)> {
+    let data = fs::read(path).ok()?;
+    let tree = usvg::Tree::from_data(&data, &usvg::Options::default()).ok()?;
+    Some((tree.size().width(), tree.size().height()))
+}
+
+// Helper: System fonts for text inside SVG files (loaded once per process)
+fn svg_fonts() -> Arc<usvg::fontdb::Database> {
+    static FONTS: OnceLock<Arc<usvg::fontdb::Database>> = OnceLock::new();
+    FONTS
+        .get_or_init(|| {
+            let mut database = usvg::fontdb::Database::new();
+            database.load_system_fonts();
+            Arc::new(database)
+        })
+        .clone()
+}
+
+// Helper: Rasterize an SVG at the size the layer draws it
+fn rasterize_svg(path: &Path, target: TargetSize) -> Result<RgbaImage, ClassifiedError> {
+    let data = fs::read(path)?;
+    let options = usvg::Options {
+        resources_dir: path.parent().map(|p| p.to_path_buf()), // Relative <image> references
+        fontdb: svg_fonts(),
+        ..usvg::Options::default()
+    };
+    let tree = usvg::Tree::from_data(&data, &options).map_err(|e| ClassifiedError::new(ErrorKind::CorruptData, e))?;
+
+    let (svg_width, svg_height) = (tree.size().width(), tree.size().height());
+    let (width, height) = target.drawn_size(svg_width as f64, svg_height as f64);
+    let width = (width as f32).round().clamp(1.0, MAX_SVG_SIZE) as u32;
+    let height = (height as f32).round().clamp(1.0, MAX_SVG_SIZE) as u32;
+
+    let mut pixmap = tiny_skia::Pixmap::new(width, height)
+        .ok_or_else(|| ClassifiedError::new(ErrorKind::TooLarge, "Invalid SVG size"))?;
+    let transform = tiny_skia::Transform::from_scale(width as f32 / svg_width, height as f32 / svg_height);
+    resvg::render(&tree, transform, &mut pixmap.as_mut());
+
+    // tiny-skia stores premultiplied alpha
+    let pixels: Vec<u8> = pixmap
+        .pixels()
+        .iter()
+        .flat_map(|pixel| {
+            let color = pixel.demultiply();
+            [color.red(), color.green(), color.blue(), color.alpha()]
+        })
+        .collect();
+    RgbaImage::from_raw(width, height, pixels).ok_or_else(|| ClassifiedError::new(ErrorKind::Internal, "Invalid SVG raster"))
+}
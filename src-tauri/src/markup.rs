@@ -0,0 +1,161 @@
+use crate::parse_hex_color;
+use image::Rgba;
+
+// Characters that can be escaped with a backslash
+const MARKUP_CHARS: &[char] = &['*', '~', '_', '[', ']', '\\'];
+
+// Largest font size a [size=N] tag can request; the renderer further limits it relative to the layer
+const MAX_MARKUP_SIZE: f32 = 1000.0;
+
+// A span of text sharing one style, produced from table value markup
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TextRun {
+    pub text: String,
+    pub bold: bool,
+    pub strikethrough: bool,
+    pub underline: bool,
+    pub color: Option<Rgba<u8>>, // None = layer fill
+    pub size: Option<f32>,       // None = layer font size
+}
+
+#[derive(Default)]
+struct RunState {
+    bold: bool,
+    strikethrough: bool,
+    underline: bool,
+    colors: Vec<Rgba<u8>>,
+    sizes: Vec<f32>,
+}
+
+impl RunState {
+    fn run(&self, text: String) -> TextRun {
+        TextRun {
+            text,
+            bold: self.bold,
+            strikethrough: self.strikethrough,
+            underline: self.underline,
+            color: self.colors.last().copied(),
+            size: self.sizes.last().copied(),
+        }
+    }
+}
+
+// Text of a layer without markup: a single run in the layer style
+pub fn plain_text(input: &str) -> Vec<TextRun> {
+    vec![TextRun {
+        text: input.to_string(),
+        ..TextRun::default()
+    }]
+}
+
+// Parse lightweight text markup into styled runs.
+//
+// Supported syntax:
+//   **bold**, ~~strikethrough~~, __underline__
+//   [color=#RRGGBB]...[/color], [size=48]...[/size] (sizes are capped at MAX_MARKUP_SIZE)
+//   \* escapes a markup character (one of * ~ _ [ ] \); other backslashes are literal
+// Anything that is not recognized is kept as literal text.
+pub fn parse_markup(input: &str) -> Vec<TextRun> {
+    let mut runs: Vec<TextRun> = Vec::new();
+    let mut state = RunState::default();
+    let mut current = String::new();
+
+    fn flush(runs: &mut Vec<TextRun>, state: &RunState, current: &mut String) {
+        if !current.is_empty() {
+            runs.push(state.run(std::mem::take(current)));
+        }
+    }
+
+    let mut rest = input;
+    while let Some(c) = rest.chars().next() {
+        // Escaped markup character (`C:\path` keeps its backslash)
+        if c == '\\' {
+            let mut chars = rest.chars();
+            chars.next();
+            if let Some(escaped) = chars.next().filter(|e| MARKUP_CHARS.contains(e)) {
+                current.push(escaped);
+                rest = chars.as_str();
+                continue;
+            }
+        }
+
+        // Toggle markers
+        if let Some(marker) = ["**", "~~", "__"].into_iter().find(|m| rest.starts_with(m)) {
+            flush(&mut runs, &state, &mut current);
+            match marker {
+                "**" => state.bold = !state.bold,
+                "~~" => state.strikethrough = !state.strikethrough,
+                _ => state.underline = !state.underline,
+            }
+            rest = &rest[marker.len()..];
+            continue;
+        }
+
+        // Tags
+        if c == '[' {
+            if let Some(end) = rest.find(']') {
+                let tag = &rest[1..end];
+                let handled = match tag.split_once('=') {
+                    Some(("color", value)) => {
+                        flush(&mut runs, &state, &mut current);
+                        state.colors.push(parse_hex_color(value.trim()));
+                        true
+                    }
+                    Some(("size", value)) => match value.trim().parse::<f32>() {
+                        Ok(size) if size.is_finite() && size > 0.0 => {
+                            flush(&mut runs, &state, &mut current);
+                            state.sizes.push(size.min(MAX_MARKUP_SIZE));
+                            true
+                        }
+                        _ => false,
+                    },
+                    _ => match tag {
+                        "/color" => {
+                            flush(&mut runs, &state, &mut current);
+                            state.colors.pop();
+                            true
+                        }
+                        "/size" => {
+                            flush(&mut runs, &state, &mut current);
+                            state.sizes.pop();
+                            true
+                        }
+                        _ => false,
+                    },
+                };
+                if handled {
+                    rest = &rest[end + 1..];
+                    continue;
+                }
+            }
+        }
+
+        current.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+
+    flush(&mut runs, &state, &mut current);
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn huge_size_is_capped() {
+        let runs = parse_markup("[size=200000]X[/size]");
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].size, Some(MAX_MARKUP_SIZE));
+    }
+
+    #[test]
+    fn non_finite_size_is_literal_text() {
+        for input in ["[size=inf]X", "[size=NaN]X", "[size=-5]X"] {
+            let runs = parse_markup(input);
+            assert_eq!(runs.len(), 1, "{}", input);
+            assert_eq!(runs[0].text, input);
+            assert_eq!(runs[0].size, None);
+        }
+    }
+}
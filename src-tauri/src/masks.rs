@@ -0,0 +1,35 @@
+use image::RgbaImage;
+
+// How a mask image's pixels become coverage
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MaskMode {
+    Alpha,     // Opacity of the mask
+    Luminance, // Brightness of the mask times its opacity (CSS mask-mode: luminance)
+}
+
+impl MaskMode {
+    pub fn parse(value: Option<&str>) -> Self {
+        match value {
+            Some("luminance") => MaskMode::Luminance,
+            _ => MaskMode::Alpha,
+        }
+    }
+}
+
+// Multiply a layer's alpha by the coverage of a mask of the same size (both canvas-sized, straight alpha)
+pub fn apply_mask(layer: &mut RgbaImage, mask: &RgbaImage, mode: MaskMode, invert: bool) {
+    for (pixel, m) in layer.pixels_mut().zip(mask.pixels()) {
+        if pixel[3] == 0 {
+            continue;
+        }
+        let alpha = m[3] as f64 / 255.0;
+        let coverage = match mode {
+            MaskMode::Alpha => alpha,
+            MaskMode::Luminance => {
+                (0.2125 * m[0] as f64 + 0.7154 * m[1] as f64 + 0.0721 * m[2] as f64) / 255.0 * alpha
+            }
+        };
+        let coverage = if invert { 1.0 - coverage } else { coverage };
+        pixel[3] = (pixel[3] as f64 * coverage).round() as u8;
+    }
+}
@@ -0,0 +1,92 @@
+use crate::loader::{is_svg, jpeg_scale_denominator, TargetSize};
+use crate::metadata::{read_image_metadata, ImageMetadata};
+use crate::ImageFileInfo;
+use std::path::Path;
+use sysinfo::System;
+
+const MIB: u64 = 1024 * 1024;
+const DEFAULT_BUDGET_SHARE: f64 = 0.5; // Share of currently available RAM a batch may use
+const FALLBACK_BUDGET_MB: u64 = 2048; // When available RAM cannot be read
+const FRAME_BYTES_PER_PIXEL: u64 = 52; // Canvas, layer/mask/clip buffers, float resampling buffers and the encoded output
+
+// Memory budget for a batch in MiB: the requested value, or half of the available RAM
+pub fn memory_budget_mb(requested: Option<u64>) -> u64 {
+    let budget = requested.unwrap_or_else(|| {
+        let mut system = System::new();
+        system.refresh_memory();
+        match system.available_memory() {
+            0 => FALLBACK_BUDGET_MB,
+            available => (available as f64 * DEFAULT_BUDGET_SHARE) as u64 / MIB,
+        }
+    });
+    budget.max(1)
+}
+
+// Estimated peak memory (MiB) of rendering one image, from header dimensions only.
+// `hero` is the box the image is drawn into (None when the template has no hero layer);
+// `preprocess_bytes_per_pixel` is the extra working memory of hero preprocessing (e.g. keying);
+// `layer_bytes` is the largest decoded image layer of the template (see `layer_bytes`).
+// Layers are decoded one at a time, so only the largest source counts towards the peak.
+pub fn estimate_memory_mb(
+    image: &ImageFileInfo,
+    hero: Option<TargetSize>,
+    preprocess_bytes_per_pixel: u64,
+    layer_bytes: u64,
+    frame_size: (u32, u32),
+) -> u64 {
+    let frame_bytes = frame_size.0 as u64 * frame_size.1 as u64 * FRAME_BYTES_PER_PIXEL;
+    let hero_bytes = match hero {
+        Some(target) => decoded_bytes(Path::new(&image.path), &image.metadata, target, preprocess_bytes_per_pixel),
+        None => 0,
+    };
+    (frame_bytes + hero_bytes.max(layer_bytes)).div_ceil(MIB).max(1)
+}
+
+// Decoded size of a template's image layer (background, image or mask asset), reading its header.
+// The same for every image of a batch, so it is computed once.
+pub fn layer_bytes(path: &Path, target: TargetSize, preprocess_bytes_per_pixel: u64) -> u64 {
+    decoded_bytes(path, &ImageMetadata::default(), target, preprocess_bytes_per_pixel)
+}
+
+// Helper: Decoded source size plus its RGBA copy and preprocessing buffers, taking reduced JPEG decoding into account.
+// Images without metadata (e.g. from jobs saved before it was sent along) have their header read here.
+fn decoded_bytes(path: &Path, metadata: &ImageMetadata, target: TargetSize, preprocess_bytes_per_pixel: u64) -> u64 {
+    let read;
+    let metadata: &ImageMetadata = if metadata.width.is_some() {
+        metadata
+    } else {
+        read = read_image_metadata(path);
+        &read
+    };
+
+    // SVGs are rasterized at the drawn size
+    let (width, height) = match (is_svg(path), metadata.width, metadata.height) {
+        (true, Some(w), Some(h)) => {
+            let (w, h) = target.drawn_size(w as f64, h as f64);
+            (w.max(1.0) as u64, h.max(1.0) as u64)
+        }
+        (false, Some(w), Some(h)) => {
+            // Header dimensions are as displayed, like the target box
+            let is_jpeg = path
+                .extension()
+                .map(|ext| matches!(ext.to_string_lossy().to_lowercase().as_str(), "jpg" | "jpeg"))
+                .unwrap_or(false);
+            let denominator = if is_jpeg { jpeg_scale_denominator(w, h, target) as u64 } else { 1 };
+            ((w as u64).div_ceil(denominator), (h as u64).div_ceil(denominator))
+        }
+        _ => return 0, // Unknown size: the image will fail to decode anyway
+    };
+
+    let bytes_per_pixel = match metadata.color_type.as_deref() {
+        Some("l8") => 1,
+        Some("la8") | Some("l16") => 2,
+        Some("rgb8") => 3,
+        Some("rgba8") | Some("la16") => 4,
+        Some("rgb16") => 6,
+        Some("rgba16") => 8,
+        Some("rgb32f") => 12,
+        Some("rgba32f") => 16,
+        _ => 4,
+    };
+    width * height * (bytes_per_pixel + 4 + preprocess_bytes_per_pixel)
+}
@@ -0,0 +1,65 @@
+use crate::loader::{is_svg, svg_size};
+use image::{ImageDecoder, ImageReader};
+use std::fs;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+// Image properties read from file headers (pixel data is not decoded)
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default)]
+pub struct ImageMetadata {
+    pub width: Option<u32>,  // As displayed (after EXIF orientation)
+    pub height: Option<u32>,
+    pub file_size: Option<u64>,   // Bytes
+    pub modified: Option<u64>,    // Unix time, milliseconds
+    pub color_type: Option<String>, // e.g. "rgb8", "rgba8", "l16"
+    pub has_alpha: Option<bool>,
+    pub orientation: Option<u8>, // EXIF orientation (1 = upright, 2-8 = flipped/rotated)
+}
+
+// Read image metadata; fields that cannot be determined are left empty
+pub fn read_image_metadata(path: &Path) -> ImageMetadata {
+    let mut metadata = ImageMetadata::default();
+
+    if let Ok(file) = fs::metadata(path) {
+        metadata.file_size = Some(file.len());
+        metadata.modified = file
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|duration| duration.as_millis() as u64);
+    }
+
+    // Vector images report their intrinsic size (rounded up to whole pixels)
+    if is_svg(path) {
+        if let Some((width, height)) = svg_size(path) {
+            metadata.width = Some(width.ceil() as u32);
+            metadata.height = Some(height.ceil() as u32);
+            metadata.color_type = Some("svg".to_string());
+            metadata.has_alpha = Some(true);
+        }
+        return metadata;
+    }
+
+    let decoder = ImageReader::open(path)
+        .and_then(|reader| reader.with_guessed_format())
+        .ok()
+        .and_then(|reader| reader.into_decoder().ok());
+
+    if let Some(mut decoder) = decoder {
+        let (width, height) = decoder.dimensions();
+        let color = decoder.original_color_type();
+        let has_alpha = decoder.color_type().has_alpha();
+        let orientation = decoder.orientation().map(|o| o.to_exif()).unwrap_or(1);
+
+        // Orientations 5-8 rotate by 90 degrees, swapping the displayed width and height
+        let (width, height) = if orientation >= 5 { (height, width) } else { (width, height) };
+
+        metadata.width = Some(width);
+        metadata.height = Some(height);
+        metadata.color_type = Some(format!("{:?}", color).to_lowercase());
+        metadata.has_alpha = Some(has_alpha);
+        metadata.orientation = Some(orientation);
+    }
+
+    metadata
+}
@@ -0,0 +1,120 @@
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Instant;
+
+const RATE_WINDOW: usize = 20; // Completions used for the rolling rate (smooths out slow/fast images)
+
+// Payload of the `generation-progress` event
+#[derive(Clone, Serialize, Debug)]
+pub struct GenerationProgress {
+    current: usize, // Images finished (including failed)
+    total: usize,
+    current_file: String, // Last file started or finished
+    elapsed_ms: u64,
+    eta_ms: Option<u64>, // None until the rate is known
+    images_per_second: f64,
+    bytes_written: u64,
+    failed: usize,
+    workers: Vec<Option<String>>, // File each worker is processing (None = idle)
+}
+
+struct ProgressState {
+    finished: usize,
+    failed: usize,
+    bytes_written: u64,
+    recent: VecDeque<Instant>, // Completion times of the last RATE_WINDOW images
+    workers: Vec<Option<String>>,
+}
+
+// Progress of one batch, shared by all workers
+pub struct ProgressTracker {
+    started: Instant,
+    total: usize,
+    state: Mutex<ProgressState>,
+}
+
+impl ProgressTracker {
+    pub fn new(total: usize, workers: usize) -> ProgressTracker {
+        ProgressTracker {
+            started: Instant::now(),
+            total,
+            state: Mutex::new(ProgressState {
+                finished: 0,
+                failed: 0,
+                bytes_written: 0,
+                recent: VecDeque::with_capacity(RATE_WINDOW + 1),
+                workers: vec![None; workers.max(1)],
+            }),
+        }
+    }
+
+    // Assign a worker slot to a file; returns the slot and the updated progress
+    pub fn start(&self, file: &str) -> (usize, GenerationProgress) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let slot = match state.workers.iter().position(|w| w.is_none()) {
+            Some(slot) => slot,
+            None => {
+                state.workers.push(None);
+                state.workers.len() - 1
+            }
+        };
+        state.workers[slot] = Some(file.to_string());
+        (slot, self.snapshot(&state, file))
+    }
+
+    // Record a finished file (bytes = None when it failed) and free its worker slot
+    pub fn finish(&self, slot: usize, file: &str, bytes: Option<u64>) -> GenerationProgress {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.finished += 1;
+        match bytes {
+            Some(bytes) => state.bytes_written += bytes,
+            None => state.failed += 1,
+        }
+        state.recent.push_back(Instant::now());
+        if state.recent.len() > RATE_WINDOW {
+            state.recent.pop_front();
+        }
+        if let Some(worker) = state.workers.get_mut(slot) {
+            *worker = None;
+        }
+        self.snapshot(&state, file)
+    }
+
+    // Helper: Images per second over the recent window (whole run until the window has two points)
+    fn rate(&self, state: &ProgressState) -> f64 {
+        let window = match (state.recent.front(), state.recent.back()) {
+            (Some(first), Some(last)) if state.recent.len() >= 2 => {
+                Some(((state.recent.len() - 1) as f64, last.duration_since(*first).as_secs_f64()))
+            }
+            _ => None,
+        };
+        let (images, seconds) = match window {
+            Some((images, seconds)) if seconds > 0.0 => (images, seconds),
+            _ => (state.finished as f64, self.started.elapsed().as_secs_f64()),
+        };
+        if seconds > 0.0 {
+            images / seconds
+        } else {
+            0.0
+        }
+    }
+
+    fn snapshot(&self, state: &ProgressState, file: &str) -> GenerationProgress {
+        let rate = self.rate(state);
+        let remaining = self.total.saturating_sub(state.finished);
+        let eta_ms = (state.finished > 0 && rate > 0.0).then(|| (remaining as f64 / rate * 1000.0) as u64);
+
+        GenerationProgress {
+            current: state.finished,
+            total: self.total,
+            current_file: file.to_string(),
+            elapsed_ms: self.started.elapsed().as_millis() as u64,
+            eta_ms,
+            images_per_second: rate,
+            bytes_written: state.bytes_written,
+            failed: state.failed,
+            workers: state.workers.clone(),
+        }
+    }
+}
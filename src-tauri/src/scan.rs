@@ -0,0 +1,166 @@
+use crate::metadata::read_image_metadata;
+use crate::{ImageFileInfo, FONTS_DIR, JOBS_DIR, OUTPUT_DIR, TEMPLATES_DIR, THUMBNAILS_DIR};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+const IMAGE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "gif", "webp", "bmp", "svg", "svgz"];
+
+// App folders inside the working directory that never contain source images
+const APP_DIRS: &[&str] = &[OUTPUT_DIR, TEMPLATES_DIR, FONTS_DIR, THUMBNAILS_DIR, JOBS_DIR];
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanOptions {
+    #[serde(default)]
+    pub recursive: bool,
+    pub max_depth: Option<usize>, // Subfolder levels below the root (None = unlimited)
+    #[serde(default)]
+    pub include: Vec<String>, // Glob patterns on the relative path; empty = all images
+    #[serde(default)]
+    pub exclude: Vec<String>, // Glob patterns for files and folders to skip
+}
+
+// Whether a path lies inside one of the app's own folders in the working directory
+pub fn is_app_path(root: &Path, path: &Path) -> bool {
+    path.strip_prefix(root)
+        .ok()
+        .and_then(|relative| relative.components().next())
+        .map(|first| APP_DIRS.iter().any(|dir| first.as_os_str() == *dir))
+        .unwrap_or(false)
+}
+
+// Helper: Compile glob patterns (matched against relative paths with `/` separators)
+fn build_globs(patterns: &[String]) -> Result<GlobSet, String> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns.iter().map(|p| p.trim()).filter(|p| !p.is_empty()) {
+        let glob = Glob::new(pattern).map_err(|e| format!("Invalid pattern '{}': {}", pattern, e))?;
+        builder.add(glob);
+    }
+    builder.build().map_err(|e| e.to_string())
+}
+
+// Find images under the root directory (with header metadata), sorted by relative path
+pub fn scan_images(root: &Path, options: &ScanOptions) -> Result<Vec<ImageFileInfo>, String> {
+    Scanner::new(root, options)?.scan(root)
+}
+
+// Compiled scan options: full scans and rescans of single changed paths apply the same rules
+pub struct Scanner {
+    root: PathBuf,
+    include: GlobSet,
+    exclude: GlobSet,
+    max_depth: usize, // Depth 1 = files directly inside the root
+}
+
+impl Scanner {
+    pub fn new(root: &Path, options: &ScanOptions) -> Result<Self, String> {
+        Ok(Scanner {
+            root: root.to_path_buf(),
+            include: build_globs(&options.include)?,
+            exclude: build_globs(&options.exclude)?,
+            max_depth: if options.recursive {
+                options.max_depth.map(|d| d.saturating_add(1)).unwrap_or(usize::MAX)
+            } else {
+                1
+            },
+        })
+    }
+
+    // Path relative to the root, `/`-separated
+    fn relative(&self, path: &Path) -> String {
+        path.strip_prefix(&self.root)
+            .unwrap_or(path)
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    // Whether a relative path or one of the folders it lies in is excluded (app folders always are)
+    fn is_excluded(&self, relative_path: &str) -> bool {
+        if relative_path.split('/').next().is_some_and(|first| APP_DIRS.contains(&first)) {
+            return true;
+        }
+        relative_path
+            .match_indices('/')
+            .map(|(end, _)| &relative_path[..end])
+            .chain(std::iter::once(relative_path))
+            .any(|prefix| self.exclude.is_match(prefix))
+    }
+
+    // Whether a file is part of the scan, by its extension and relative path
+    fn is_image(&self, path: &Path, relative_path: &str) -> bool {
+        let is_image = path
+            .extension()
+            .map(|ext| IMAGE_EXTENSIONS.contains(&ext.to_string_lossy().to_lowercase().as_str()))
+            .unwrap_or(false);
+        is_image && (self.include.is_empty() || self.include.is_match(relative_path))
+    }
+
+    fn image_info(&self, path: &Path, relative_path: String) -> ImageFileInfo {
+        ImageFileInfo {
+            path: path.to_string_lossy().to_string(),
+            name: path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+            relative_path,
+            metadata: read_image_metadata(path),
+        }
+    }
+
+    // Find images in `dir` (the root or a folder inside it), sorted by relative path
+    pub fn scan(&self, dir: &Path) -> Result<Vec<ImageFileInfo>, String> {
+        let dir_relative = self.relative(dir);
+        let dir_depth = if dir_relative.is_empty() { 0 } else { dir_relative.split('/').count() };
+        if dir_depth > 0 && self.is_excluded(&dir_relative) {
+            return Ok(Vec::new());
+        }
+
+        let walker = WalkDir::new(dir)
+            .min_depth(1)
+            .max_depth(self.max_depth.saturating_sub(dir_depth))
+            .follow_links(true) // Symlinked files and folders are scanned like regular ones
+            .into_iter()
+            .filter_entry(|entry| {
+                let relative_path = self.relative(entry.path());
+                if entry.file_type().is_dir() && entry.depth() + dir_depth == 1 && APP_DIRS.contains(&relative_path.as_str()) {
+                    return false;
+                }
+                !self.exclude.is_match(&relative_path)
+            });
+
+        let mut images: Vec<ImageFileInfo> = Vec::new();
+        for entry in walker {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) if e.depth() == 0 => return Err(e.to_string()),
+                Err(_) => continue, // Unreadable subfolder
+            };
+            if !entry.file_type().is_file() {
+                continue;
+            }
+
+            let relative_path = self.relative(entry.path());
+            if self.is_image(entry.path(), &relative_path) {
+                images.push(self.image_info(entry.path(), relative_path));
+            }
+        }
+
+        // Sort alphabetically by relative path (folders group together)
+        images.sort_by_key(|image| image.relative_path.to_lowercase());
+
+        Ok(images)
+    }
+
+    // Image entry of a single file, None when it is missing or not part of the scan
+    pub fn image(&self, path: &Path) -> Option<ImageFileInfo> {
+        let relative_path = self.relative(path);
+        let depth = relative_path.split('/').count();
+        let included = path.starts_with(&self.root)
+            && depth <= self.max_depth
+            && !self.is_excluded(&relative_path)
+            && self.is_image(path, &relative_path)
+            && path.is_file();
+        included.then(|| self.image_info(path, relative_path))
+    }
+}
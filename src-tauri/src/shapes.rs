@@ -0,0 +1,131 @@
+use crate::compose::{over, premultiply, Patch};
+use crate::gradient::GradientFill;
+use crate::transform::{LayerFrame, LocalRect};
+use image::Rgba;
+
+// Geometry of a shape layer, in layer-local coordinates
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShapeKind {
+    Rect,     // Rectangle filling the layer box (rounded when corner_radius > 0)
+    Ellipse,  // Ellipse inscribed in the layer box (a circle for square boxes)
+    Line,     // Horizontal line through the box center, spanning its full width
+}
+
+impl ShapeKind {
+    pub fn parse(value: Option<&str>) -> Self {
+        match value {
+            Some("ellipse") | Some("circle") => ShapeKind::Ellipse,
+            Some("line") => ShapeKind::Line,
+            _ => ShapeKind::Rect,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ShapeStyle<'a> {
+    pub kind: ShapeKind,
+    pub fill: Option<Rgba<u8>>,
+    pub fill_gradient: Option<&'a GradientFill>, // Replaces the fill color; resolved for the layer box
+    pub stroke: Option<Rgba<u8>>,
+    pub stroke_width: f64,
+    pub corner_radius: f64,
+}
+
+// Signed distance from a point to a (rounded) rectangle spanning (0, 0)..(width, height), negative inside
+pub fn rounded_rect_distance(width: f64, height: f64, corner_radius: f64, lx: f64, ly: f64) -> f64 {
+    let half_w = width / 2.0;
+    let half_h = height / 2.0;
+    let radius = corner_radius.clamp(0.0, half_w.min(half_h).max(0.0));
+    let qx = (lx - half_w).abs() - (half_w - radius);
+    let qy = (ly - half_h).abs() - (half_h - radius);
+    let outside = qx.max(0.0).hypot(qy.max(0.0));
+    outside + qx.max(qy).min(0.0) - radius
+}
+
+// Signed distance from a local point to the shape outline (negative inside)
+fn signed_distance(style: &ShapeStyle, width: f64, height: f64, lx: f64, ly: f64) -> f64 {
+    let half_w = width / 2.0;
+    let half_h = height / 2.0;
+    let px = lx - half_w;
+    let py = ly - half_h;
+
+    match style.kind {
+        ShapeKind::Rect => rounded_rect_distance(width, height, style.corner_radius, lx, ly),
+        ShapeKind::Ellipse => {
+            if half_w <= 0.0 || half_h <= 0.0 {
+                return f64::INFINITY;
+            }
+            // First-order distance estimate: implicit value divided by its gradient length
+            let f = (px / half_w).powi(2) + (py / half_h).powi(2) - 1.0;
+            let gx = 2.0 * px / (half_w * half_w);
+            let gy = 2.0 * py / (half_h * half_h);
+            let grad = gx.hypot(gy);
+            if grad < 1e-9 {
+                -half_w.min(half_h)
+            } else {
+                f / grad
+            }
+        }
+        ShapeKind::Line => {
+            let dx = (px.abs() - half_w).max(0.0);
+            dx.hypot(py)
+        }
+    }
+}
+
+// Rasterize a shape layer with anti-aliased edges, rotated with its frame
+pub fn render_shape(style: &ShapeStyle, frame: &LayerFrame, canvas_size: (u32, u32)) -> Option<Patch> {
+    let stroke_width = style.stroke_width.max(0.0);
+    let half_stroke = stroke_width / 2.0;
+
+    // Lines are drawn with the stroke (or fill) color and have no interior
+    let (fill, gradient, stroke) = match style.kind {
+        ShapeKind::Line => (None, None, style.stroke.or(style.fill)),
+        _ => (style.fill, style.fill_gradient, style.stroke.filter(|_| stroke_width > 0.0)),
+    };
+    if fill.is_none() && gradient.is_none() && stroke.is_none() {
+        return None;
+    }
+
+    // Strokes are centered on the outline, so they extend half their width past the box
+    let margin = if stroke.is_some() { half_stroke.max(0.5) } else { 0.0 };
+    let bounds = LocalRect::new(-margin, -margin, frame.width + 2.0 * margin, frame.height + 2.0 * margin);
+    let (x0, y0, x1, y1) = frame.canvas_bounds(&bounds, canvas_size)?;
+    let out_w = (x1 - x0) as usize;
+    let out_h = (y1 - y0) as usize;
+
+    let mut data = vec![[0.0f32; 4]; out_w * out_h];
+    for ry in 0..out_h {
+        for rx in 0..out_w {
+            let cx = (x0 + rx as i64) as f64 + 0.5;
+            let cy = (y0 + ry as i64) as f64 + 0.5;
+            let (lx, ly) = frame.to_local(cx, cy);
+            let d = signed_distance(style, frame.width, frame.height, lx, ly);
+
+            let mut out = [0.0f32; 4];
+
+            let coverage = (0.5 - d).clamp(0.0, 1.0) as f32;
+            if coverage > 0.0 {
+                if let Some(gradient) = gradient {
+                    let color = gradient.sample_dithered(lx, ly, x0 + rx as i64, y0 + ry as i64);
+                    out = color.map(|v| v * coverage);
+                } else if let Some(color) = fill {
+                    out = premultiply(color, coverage);
+                }
+            }
+
+            if let Some(color) = stroke {
+                let line_width = if style.kind == ShapeKind::Line { stroke_width.max(1.0) } else { stroke_width };
+                let coverage = (0.5 - (d.abs() - line_width / 2.0)).clamp(0.0, 1.0).min(line_width);
+                if coverage > 0.0 {
+                    // Stroke is painted over the fill
+                    over(&mut out, premultiply(color, coverage as f32));
+                }
+            }
+
+            data[ry * out_w + rx] = out;
+        }
+    }
+
+    Some(Patch::from_premultiplied(x0, y0, out_w as u32, out_h as u32, &data))
+}
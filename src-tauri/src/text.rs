@@ -0,0 +1,648 @@
+use crate::compose::{draw_patch, image_from_premultiplied, over, premultiply};
+use crate::effects::Shadow;
+use crate::fonts::{FontFile, FontKey, FontLibrary};
+use crate::gradient::GradientFill;
+use crate::markup::TextRun;
+use crate::shapes::rounded_rect_distance;
+use crate::transform::{render_image, LayerFrame, LocalRect};
+use ab_glyph::{point, Font, FontRef, GlyphId, GlyphImageFormat, PxScale, ScaleFont};
+use image::{GrayImage, Rgba, RgbaImage};
+use imageproc::morphology::{grayscale_dilate, Mask};
+use rustybuzz::{Direction, UnicodeBuffer};
+use unicode_bidi::BidiInfo;
+
+const TEXT_SUPERSAMPLE: f64 = 2.0; // Text is rasterized at this multiple of the output resolution
+const MIN_FONT_SIZE: f32 = 8.0; // Auto-fit never shrinks text below this size
+const MAX_RUN_SCALE: f32 = 4.0; // Runs with an explicit size are at most this multiple of the layer font size
+const FILTER_REACH: f64 = 3.0; // Widest resampling filter support, in canvas pixels
+
+// Padded rounded box drawn behind the text
+#[derive(Debug, Clone, Copy)]
+pub struct TextBackground {
+    pub color: Rgba<u8>,
+    pub padding: f64,
+    pub corner_radius: Option<f64>, // None = fully rounded pill
+}
+
+#[derive(Debug, Clone)]
+pub struct TextStyle {
+    pub font_size: f32, // Base size; runs with an explicit size scale along with auto-fit
+    pub fill: Rgba<u8>,
+    pub fill_gradient: Option<GradientFill>, // Replaces the fill color of runs without their own; resolved for the layer box
+    pub stroke: Option<Rgba<u8>>,
+    pub stroke_width: f64,
+    pub shadow: Option<Shadow>,
+    pub background: Option<TextBackground>,
+}
+
+// Font faces parsed from the batch's font library (each worker thread parses its own)
+pub struct LoadedFonts<'a> {
+    library: &'a FontLibrary,
+    faces: Vec<Option<FontFace<'a>>>, // Same order as library files; None = failed to parse
+}
+
+impl<'a> LoadedFonts<'a> {
+    pub fn load(library: &'a FontLibrary) -> Self {
+        LoadedFonts {
+            library,
+            faces: library.files.iter().map(FontFace::parse).collect(),
+        }
+    }
+
+    // Fallback chains of a layer font (bold layers use the bold chain for all runs)
+    pub fn for_layer(&self, family: &str, bold: bool) -> TextFonts<'_> {
+        let key = |bold| FontKey {
+            family: family.to_string(),
+            bold,
+        };
+        let mut regular = self.library.chain(&key(bold));
+        if regular.is_empty() {
+            regular = self.library.chain(&FontKey {
+                family: String::new(),
+                bold: false,
+            });
+        }
+        let bold_chain = self.library.chain(&key(true));
+
+        TextFonts {
+            faces: &self.faces,
+            regular,
+            bold: if bold_chain.is_empty() { regular } else { bold_chain },
+            layer_bold: bold,
+        }
+    }
+}
+
+// A parsed font face: outlines for rasterization and OpenType tables for shaping
+pub struct FontFace<'a> {
+    glyphs: FontRef<'a>,
+    shaper: rustybuzz::Face<'a>,
+    bold: bool,
+}
+
+impl<'a> FontFace<'a> {
+    fn parse(file: &'a FontFile) -> Option<Self> {
+        Some(FontFace {
+            glyphs: FontRef::try_from_slice_and_index(&file.bytes, file.index).ok()?,
+            shaper: rustybuzz::Face::from_slice(&file.bytes, file.index)?,
+            bold: file.bold,
+        })
+    }
+
+    // ab_glyph scale for a CSS-style font size (em size in pixels)
+    fn px_scale(&self, size: f32) -> PxScale {
+        let units_per_em = self.glyphs.units_per_em().unwrap_or(1000.0);
+        PxScale::from(size * self.glyphs.height_unscaled() / units_per_em)
+    }
+
+    fn covers(&self, c: char) -> bool {
+        self.shaper.glyph_index(c).is_some()
+    }
+}
+
+// Fallback chains of one text layer (indices into the loaded faces, highest priority first)
+pub struct TextFonts<'a> {
+    faces: &'a [Option<FontFace<'a>>],
+    regular: &'a [usize],
+    bold: &'a [usize],
+    layer_bold: bool,
+}
+
+impl TextFonts<'_> {
+    fn chain(&self, bold: bool) -> &[usize] {
+        if bold || self.layer_bold {
+            self.bold
+        } else {
+            self.regular
+        }
+    }
+
+    fn face(&self, index: usize) -> Option<&FontFace<'_>> {
+        self.faces.get(index).and_then(|f| f.as_ref())
+    }
+
+    // Face for a character: the current face keeps marks, joiners and anything it covers,
+    // otherwise the first face in the chain that has a glyph for it
+    fn face_for(&self, chain: &[usize], current: Option<usize>, c: char) -> Option<usize> {
+        if let Some(current) = current {
+            let keep = c.is_whitespace()
+                || is_combining(c)
+                || self.face(current).is_some_and(|f| f.covers(c));
+            if keep {
+                return Some(current);
+            }
+        }
+
+        chain
+            .iter()
+            .copied()
+            .find(|&i| self.face(i).is_some_and(|f| f.covers(c)))
+            .or(current)
+            .or_else(|| chain.iter().copied().find(|&i| self.face(i).is_some()))
+    }
+
+    // Whether a run drawn with this face has to be emboldened synthetically
+    fn faux_bold(&self, run: &TextRun, face: &FontFace) -> bool {
+        (run.bold || self.layer_bold) && !face.bold
+    }
+}
+
+// Helper: Characters that attach to the previous one and must stay in its font
+fn is_combining(c: char) -> bool {
+    matches!(c,
+        '\u{0300}'..='\u{036F}'
+        | '\u{0483}'..='\u{0489}'
+        | '\u{0591}'..='\u{05BD}'
+        | '\u{064B}'..='\u{065F}'
+        | '\u{1AB0}'..='\u{1AFF}'
+        | '\u{1DC0}'..='\u{1DFF}'
+        | '\u{200C}'..='\u{200D}'
+        | '\u{20D0}'..='\u{20FF}'
+        | '\u{FE00}'..='\u{FE0F}'
+        | '\u{FE20}'..='\u{FE2F}'
+        | '\u{1F3FB}'..='\u{1F3FF}'
+        | '\u{E0020}'..='\u{E007F}'
+        | '\u{E0100}'..='\u{E01EF}')
+}
+
+// Shaped glyph on the line, at the layer's base font size (layer pixels)
+struct LineGlyph {
+    run: usize,
+    face: usize,
+    id: u16,
+    x: f32,
+    y_offset: f32, // Upwards
+    size: f32,
+}
+
+// Horizontal extent of a run segment on the line (underline and strikethrough follow it)
+struct LineSpan {
+    run: usize,
+    x0: f32,
+    x1: f32,
+    size: f32,
+}
+
+// Runs shaped and ordered visually on a single shared baseline, at the layer's base font size
+#[derive(Default)]
+struct ShapedLine {
+    glyphs: Vec<LineGlyph>,
+    spans: Vec<LineSpan>,
+    width: f32,
+    ascent: f32,
+    descent: f32, // Negative, below the baseline
+}
+
+// Helper: Shape runs with bidi reordering, kerning and ligatures
+fn shape_line(runs: &[TextRun], fonts: &TextFonts, base_size: f32) -> ShapedLine {
+    let text: String = runs.iter().map(|r| r.text.as_str()).collect();
+
+    // Byte range of each run inside the paragraph text
+    let mut run_ranges = Vec::with_capacity(runs.len());
+    let mut offset = 0;
+    for run in runs {
+        run_ranges.push(offset..offset + run.text.len());
+        offset += run.text.len();
+    }
+
+    let mut line = ShapedLine::default();
+    let bidi = BidiInfo::new(&text, None);
+
+    for paragraph in &bidi.paragraphs {
+        let (levels, visual_runs) = bidi.visual_runs(paragraph, paragraph.range.clone());
+
+        for visual in visual_runs {
+            let rtl = levels[visual.start].is_rtl();
+
+            // Split the directional run at style boundaries; right-to-left pieces are laid out in reverse
+            let mut pieces: Vec<(usize, std::ops::Range<usize>)> = run_ranges
+                .iter()
+                .enumerate()
+                .filter_map(|(i, range)| {
+                    let start = range.start.max(visual.start);
+                    let end = range.end.min(visual.end);
+                    (start < end).then_some((i, start..end))
+                })
+                .collect();
+            if rtl {
+                pieces.reverse();
+            }
+
+            for (run_index, range) in pieces {
+                let run = &runs[run_index];
+                let size = run.size.map_or(base_size, |size| size.min(base_size * MAX_RUN_SCALE));
+                let x0 = line.width;
+
+                // Split further wherever the fallback chain switches to another face
+                let chain = fonts.chain(run.bold);
+                let mut segments: Vec<(usize, std::ops::Range<usize>)> = Vec::new();
+                for (offset, c) in text[range.clone()].char_indices() {
+                    let start = range.start + offset;
+                    let current = segments.last().map(|(face, _)| *face);
+                    let Some(face) = fonts.face_for(chain, current, c) else {
+                        continue;
+                    };
+                    match segments.last_mut() {
+                        Some((last, segment)) if *last == face => segment.end = start + c.len_utf8(),
+                        _ => segments.push((face, start..start + c.len_utf8())),
+                    }
+                }
+                if rtl {
+                    segments.reverse();
+                }
+
+                for (face_index, segment) in segments {
+                    let Some(face) = fonts.face(face_index) else {
+                        continue;
+                    };
+
+                    let scaled_font = face.glyphs.as_scaled(face.px_scale(size));
+                    line.ascent = line.ascent.max(scaled_font.ascent());
+                    line.descent = line.descent.min(scaled_font.descent());
+
+                    let mut buffer = UnicodeBuffer::new();
+                    buffer.push_str(&text[segment]);
+                    buffer.set_direction(if rtl { Direction::RightToLeft } else { Direction::LeftToRight });
+                    buffer.guess_segment_properties();
+                    let shaped = rustybuzz::shape(&face.shaper, &[], buffer);
+
+                    let units_to_px = size / face.shaper.units_per_em() as f32;
+                    for (info, position) in shaped.glyph_infos().iter().zip(shaped.glyph_positions()) {
+                        line.glyphs.push(LineGlyph {
+                            run: run_index,
+                            face: face_index,
+                            id: info.glyph_id as u16,
+                            x: line.width + position.x_offset as f32 * units_to_px,
+                            y_offset: position.y_offset as f32 * units_to_px,
+                            size,
+                        });
+                        line.width += position.x_advance as f32 * units_to_px;
+                    }
+                }
+                line.spans.push(LineSpan { run: run_index, x0, x1: line.width, size });
+            }
+        }
+    }
+
+    line
+}
+
+// Helper: Find the font size (stepping down by 1px) at which the shaped line fits within box width
+fn fit_font_size(line: &ShapedLine, max_font_size: f32, box_width: f32) -> f32 {
+    let mut font_size = max_font_size;
+
+    // Shaping is size-independent, so the line width scales linearly with the font size
+    while font_size > MIN_FONT_SIZE {
+        if line.width * font_size / max_font_size <= box_width {
+            return font_size;
+        }
+        font_size -= 1.0;
+    }
+
+    MIN_FONT_SIZE.min(max_font_size)
+}
+
+// Color text is drawn with
+#[derive(Clone, Copy)]
+enum Paint<'a> {
+    Solid(Rgba<u8>),
+    Gradient(&'a GradientFill), // Sampled in layer coordinates
+}
+
+// Supersampled text raster: glyph coverage (for outline and shadow) and colored fill
+struct TextRaster {
+    width: u32,
+    height: u32,
+    area: LocalRect, // Layer-local area covered by the buffer
+    coverage: GrayImage,
+    fill: Vec<[f32; 4]>,
+}
+
+impl TextRaster {
+    fn new(width: u32, height: u32, area: LocalRect) -> Self {
+        TextRaster {
+            width,
+            height,
+            area,
+            coverage: GrayImage::new(width, height),
+            fill: vec![[0.0; 4]; (width * height) as usize],
+        }
+    }
+
+    fn plot(&mut self, x: i64, y: i64, coverage: f32, paint: Paint) {
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return;
+        }
+        let coverage = coverage.clamp(0.0, 1.0);
+        let pixel = self.coverage.get_pixel_mut(x as u32, y as u32);
+        pixel[0] = pixel[0].max((coverage * 255.0).round() as u8);
+        let color = match paint {
+            Paint::Solid(color) => premultiply(color, coverage),
+            Paint::Gradient(gradient) => {
+                let lx = self.area.x + (x as f64 + 0.5) * self.area.width / self.width as f64;
+                let ly = self.area.y + (y as f64 + 0.5) * self.area.height / self.height as f64;
+                gradient.sample_dithered(lx, ly, x, y).map(|c| c * coverage)
+            }
+        };
+        over(&mut self.fill[(y as u32 * self.width + x as u32) as usize], color);
+    }
+
+    // Draw a glyph with its origin at `pen` (x, baseline)
+    fn draw_glyph(&mut self, face: &FontFace, id: u16, size: f32, pen: (f32, f32), embolden: f32, paint: Paint) {
+        // Color bitmap glyphs (emoji fonts) have no outline
+        if face.glyphs.outline(GlyphId(id)).is_none() && self.draw_bitmap_glyph(face, id, size, pen) {
+            return;
+        }
+
+        // Synthetic bold: repeat the glyph with small horizontal offsets
+        let mut offset = 0.0;
+        loop {
+            let glyph = GlyphId(id).with_scale_and_position(face.px_scale(size), point(pen.0 + offset, pen.1));
+            if let Some(outlined) = face.glyphs.outline_glyph(glyph) {
+                let bounds = outlined.px_bounds();
+                outlined.draw(|gx, gy, coverage| {
+                    self.plot(
+                        bounds.min.x as i64 + gx as i64,
+                        bounds.min.y as i64 + gy as i64,
+                        coverage,
+                        paint,
+                    );
+                });
+            }
+            if offset >= embolden {
+                break;
+            }
+            offset = (offset + 1.0).min(embolden);
+        }
+    }
+
+    // Draw an embedded PNG glyph scaled to the font size; false if the face has none
+    fn draw_bitmap_glyph(&mut self, face: &FontFace, id: u16, size: f32, pen: (f32, f32)) -> bool {
+        let strike = size.ceil().clamp(1.0, u16::MAX as f32) as u16;
+        let Some(glyph) = face.glyphs.glyph_raster_image2(GlyphId(id), strike) else {
+            return false;
+        };
+        if !matches!(glyph.format, GlyphImageFormat::Png) {
+            return false;
+        }
+        let Ok(bitmap) = image::load_from_memory_with_format(glyph.data, image::ImageFormat::Png) else {
+            return false;
+        };
+
+        let bitmap = bitmap.to_rgba8();
+        let scale = size / glyph.pixels_per_em.max(1) as f32;
+        let width = (bitmap.width() as f32 * scale).round().max(1.0) as u32;
+        let height = (bitmap.height() as f32 * scale).round().max(1.0) as u32;
+        let scaled = image::imageops::resize(&bitmap, width, height, image::imageops::FilterType::Triangle);
+
+        // Origin is the offset of the bitmap's bottom-left corner from the pen position (y up)
+        let left = (pen.0 + glyph.origin.x * scale).round() as i64;
+        let top = (pen.1 - glyph.origin.y * scale).round() as i64 - height as i64;
+        for (x, y, pixel) in scaled.enumerate_pixels() {
+            let coverage = pixel[3] as f32 / 255.0;
+            if coverage > 0.0 {
+                self.plot(left + x as i64, top + y as i64, coverage, Paint::Solid(Rgba([pixel[0], pixel[1], pixel[2], 255])));
+            }
+        }
+        true
+    }
+
+    // Fill an axis-aligned rectangle (used for underline and strikethrough) with anti-aliased edges
+    fn fill_rect(&mut self, x0: f32, y0: f32, x1: f32, y1: f32, paint: Paint) {
+        for py in y0.floor() as i64..y1.ceil() as i64 {
+            let cover_y = (y1.min(py as f32 + 1.0) - y0.max(py as f32)).clamp(0.0, 1.0);
+            for px in x0.floor() as i64..x1.ceil() as i64 {
+                let cover_x = (x1.min(px as f32 + 1.0) - x0.max(px as f32)).clamp(0.0, 1.0);
+                self.plot(px, py, cover_x * cover_y, paint);
+            }
+        }
+    }
+}
+
+// Draw a text layer (shadow, background, outline, fill) onto the canvas, auto-fitting to the box width
+pub fn draw_text_layer(
+    canvas: &mut RgbaImage,
+    runs: &[TextRun],
+    frame: &LayerFrame,
+    style: &TextStyle,
+    fonts: &TextFonts,
+    opacity: f64,
+) {
+    if runs.is_empty() {
+        return;
+    }
+
+    let padding = style.background.map(|b| b.padding.max(0.0)).unwrap_or(0.0);
+    let stroke_width = if style.stroke.is_some() { style.stroke_width.max(0.0) } else { 0.0 };
+
+    // Auto-fit font size to the box width (inside the background padding)
+    let available_width = (frame.width - 2.0 * padding).max(1.0) as f32;
+    let line = shape_line(runs, fonts, style.font_size);
+    let font_size = fit_font_size(&line, style.font_size, available_width);
+    let factor = font_size / style.font_size;
+    let line_width = line.width * factor;
+    let line_ascent = line.ascent * factor;
+    let line_height = ((line.ascent - line.descent) * factor) as f64;
+
+    // Local area covered by the layer and its effects
+    let pill = LocalRect::new(0.0, 0.0, line_width as f64 + 2.0 * padding, line_height + 2.0 * padding);
+    let margin = stroke_width + style.shadow.map(|s| s.extent()).unwrap_or(0.0) + 1.0;
+    let content_width = frame.width.max(pill.width);
+    let content_height = frame.height.max(pill.height);
+    let area = LocalRect::new(-margin, -margin, content_width + 2.0 * margin, content_height + 2.0 * margin);
+
+    // Only the part that can reach the canvas is rasterized (effects and the filter reach past its edges)
+    let canvas_rect = frame.canvas_rect(canvas.dimensions());
+    let reach = margin + FILTER_REACH;
+    let area = area.intersect(&LocalRect::new(
+        canvas_rect.x - reach,
+        canvas_rect.y - reach,
+        canvas_rect.width + 2.0 * reach,
+        canvas_rect.height + 2.0 * reach,
+    ));
+    if area.width <= 0.0 || area.height <= 0.0 {
+        return;
+    }
+
+    // Text is rasterized at a higher resolution and resampled onto the canvas together with rotation
+    let ss = TEXT_SUPERSAMPLE;
+    let buffer_width = (area.width * ss).ceil().max(1.0) as u32;
+    let buffer_height = (area.height * ss).ceil().max(1.0) as u32;
+    let area = LocalRect::new(area.x, area.y, buffer_width as f64 / ss, buffer_height as f64 / ss);
+
+    let mut raster = TextRaster::new(buffer_width, buffer_height, area);
+    let paint = |run: &TextRun| match (run.color, &style.fill_gradient) {
+        (Some(color), _) => Paint::Solid(color),
+        (None, Some(gradient)) => Paint::Gradient(gradient),
+        (None, None) => Paint::Solid(style.fill),
+    };
+    let origin_x = ((padding - area.x) * ss) as f32;
+    let baseline = ((padding - area.y) * ss) as f32 + line_ascent * ss as f32;
+    let to_buffer = factor * ss as f32;
+
+    for glyph in &line.glyphs {
+        let run = &runs[glyph.run];
+        let Some(face) = fonts.face(glyph.face) else {
+            continue;
+        };
+        let size = glyph.size * to_buffer;
+        let embolden = if fonts.faux_bold(run, face) { (size / 32.0).max(1.0) } else { 0.0 };
+        let pen = (origin_x + glyph.x * to_buffer, baseline - glyph.y_offset * to_buffer);
+        raster.draw_glyph(face, glyph.id, size, pen, embolden, paint(run));
+    }
+
+    for span in &line.spans {
+        let run = &runs[span.run];
+        let size = span.size * to_buffer;
+        let thickness = (size / 16.0).max(1.0);
+        let (x0, x1) = (origin_x + span.x0 * to_buffer, origin_x + span.x1 * to_buffer);
+        if run.strikethrough {
+            let center = baseline - size * 0.3;
+            raster.fill_rect(x0, center - thickness / 2.0, x1, center + thickness / 2.0, paint(run));
+        }
+        if run.underline {
+            let top = baseline + size * 0.08;
+            raster.fill_rect(x0, top, x1, top + thickness, paint(run));
+        }
+    }
+
+    // Outline grows the glyph coverage outwards by the stroke width
+    let outline = if stroke_width > 0.0 {
+        let radius = (stroke_width * ss).round().clamp(1.0, 255.0) as u8;
+        Some(grayscale_dilate(&raster.coverage, &Mask::disk(radius)))
+    } else {
+        None
+    };
+
+    let background = style.background.map(|bg| {
+        let radius = bg.corner_radius.unwrap_or(pill.height / 2.0);
+        let mask = GrayImage::from_fn(buffer_width, buffer_height, |bx, by| {
+            let lx = area.x + (bx as f64 + 0.5) / ss;
+            let ly = area.y + (by as f64 + 0.5) / ss;
+            let d = rounded_rect_distance(pill.width, pill.height, radius, lx, ly);
+            image::Luma([((0.5 - d * ss).clamp(0.0, 1.0) * 255.0).round() as u8])
+        });
+        (bg.color, mask)
+    });
+
+    // Shadow is cast by the background box when present, otherwise by the outlined glyphs
+    let shadow = style.shadow.map(|shadow| {
+        let silhouette = match (&background, &outline) {
+            (Some((_, mask)), _) => mask.clone(),
+            (None, Some(outline)) => outline.clone(),
+            (None, None) => raster.coverage.clone(),
+        };
+        (shadow.color, shadow.cast(&silhouette, ss))
+    });
+
+    let alpha_at = |mask: &GrayImage, i: usize| mask.as_raw()[i] as f32 / 255.0;
+    let mut data = vec![[0.0f32; 4]; (buffer_width * buffer_height) as usize];
+    for (i, out) in data.iter_mut().enumerate() {
+        if let Some((color, mask)) = &shadow {
+            over(out, premultiply(*color, alpha_at(mask, i)));
+        }
+        if let Some((color, mask)) = &background {
+            over(out, premultiply(*color, alpha_at(mask, i)));
+        }
+        if let (Some(color), Some(mask)) = (style.stroke, &outline) {
+            over(out, premultiply(color, alpha_at(mask, i)));
+        }
+        over(out, raster.fill[i]);
+    }
+
+    let buffer = image_from_premultiplied(buffer_width, buffer_height, &data);
+    if let Some(patch) = render_image(&buffer, frame, area, canvas.dimensions()) {
+        draw_patch(canvas, &patch, opacity);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fonts::load_bundled_fonts;
+    use crate::markup::parse_markup;
+    use std::collections::{BTreeSet, HashMap};
+    use std::path::Path;
+
+    fn style() -> TextStyle {
+        TextStyle {
+            font_size: 32.0,
+            fill: Rgba([0, 0, 0, 255]),
+            fill_gradient: None,
+            stroke: None,
+            stroke_width: 0.0,
+            shadow: None,
+            background: None,
+        }
+    }
+
+    // Helper: Draw a table value with markup onto a small canvas, using the bundled test font
+    fn render(value: &str) -> RgbaImage {
+        render_styled(value, &style())
+    }
+
+    fn render_styled(value: &str, style: &TextStyle) -> RgbaImage {
+        let (bundled, warnings) = load_bundled_fonts(&Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures"));
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        let runs = parse_markup(value);
+        let key = FontKey {
+            family: "Open Sans".to_string(),
+            bold: false,
+        };
+        let chars: BTreeSet<char> = runs.iter().flat_map(|r| r.text.chars()).collect();
+        let (library, _) = FontLibrary::resolve(&HashMap::from([(key, chars)]), &[], &bundled).expect("fonts resolve");
+        let fonts = LoadedFonts::load(&library);
+
+        let mut canvas = RgbaImage::new(200, 100);
+        let frame = LayerFrame::new(10.0, 10.0, 180.0, 60.0, 0.0);
+        draw_text_layer(&mut canvas, &runs, &frame, style, &fonts.for_layer("Open Sans", false), 1.0);
+        canvas
+    }
+
+    fn has_ink(canvas: &RgbaImage) -> bool {
+        canvas.pixels().any(|p| p[3] > 0)
+    }
+
+    #[test]
+    fn huge_run_size_is_limited() {
+        let start = std::time::Instant::now();
+        assert!(has_ink(&render("[size=200000]X[/size]")));
+        assert!(start.elapsed().as_secs() < 10, "took {:?}", start.elapsed());
+    }
+
+    #[test]
+    fn infinite_run_size_renders_as_text() {
+        assert!(has_ink(&render("[size=inf]X")));
+    }
+
+    #[test]
+    fn gradient_fill_spans_the_layer_box() {
+        use crate::gradient::{Gradient, GradientStop};
+        let stop = |color: &str, position| GradientStop {
+            color: color.to_string(),
+            position,
+        };
+        let gradient = Gradient {
+            kind: "linear".to_string(),
+            angle: Some(90.0),
+            stops: vec![stop("#ff0000", 0.0), stop("#0000ff", 1.0)],
+            center_x: None,
+            center_y: None,
+        };
+        let style = TextStyle {
+            fill_gradient: Some(GradientFill::new(&gradient, 180.0, 60.0, crate::parse_hex_color)),
+            ..style()
+        };
+
+        // Runs with their own color keep it
+        let canvas = render_styled("IIIIIIIIIIII[color=#00ff00]I[/color]", &style);
+        let columns: Vec<(u32, Rgba<u8>)> = (0..canvas.width())
+            .filter_map(|x| (0..canvas.height()).map(|y| *canvas.get_pixel(x, y)).find(|p| p[3] == 255).map(|p| (x, p)))
+            .collect();
+        let (_, first) = columns[0];
+        let (_, last) = columns[columns.len() - 1];
+        assert!(first[0] > first[2], "left edge is red: {:?}", first);
+        assert_eq!(last, Rgba([0, 255, 0, 255]));
+        assert!(columns.iter().any(|(_, p)| p[2] > p[0] && p[1] == 0), "blue towards the right");
+    }
+}
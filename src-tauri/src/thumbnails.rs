@@ -0,0 +1,114 @@
+use crate::loader::{load_image, LoadOptions, TargetSize};
+use image::codecs::jpeg::JpegEncoder;
+use image::ImageFormat;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+
+const MIN_SIZE: u32 = 16;
+const MAX_SIZE: u32 = 1024;
+const JPEG_QUALITY: u8 = 80;
+const LOCK_COUNT: usize = 64;
+
+// Striped locks: a thumbnail requested while it is being prefetched is generated only once
+static LOCKS: [Mutex<()>; LOCK_COUNT] = [const { Mutex::new(()) }; LOCK_COUNT];
+static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+// Helper: FNV-1a hash (stable across builds, unlike DefaultHasher, so cache names survive updates)
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &b| (hash ^ b as u64).wrapping_mul(0x100000001b3))
+}
+
+// Helper: Cache key of a source image at a thumbnail size: (path hash, `<path hash>-<size>-` prefix, full key)
+fn cache_key(source: &Path, size: u32) -> Result<(u64, String, String), String> {
+    let modified = fs::metadata(source)
+        .and_then(|m| m.modified())
+        .map_err(|e| e.to_string())?
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+
+    let path_hash = fnv1a(source.to_string_lossy().as_bytes());
+    let prefix = format!("{:016x}-{}-", path_hash, size);
+    let key = format!("{}{}", prefix, modified);
+    Ok((path_hash, prefix, key))
+}
+
+// Return the cached thumbnail of a source image, generating it when missing or stale.
+// Cache files are named `<path hash>-<size>-<mtime>.<ext>`, so a changed source gets a new entry
+// (older entries are removed by remove_stale_thumbnails).
+pub fn get_or_create_thumbnail(cache_dir: &Path, source: &Path, size: u32) -> Result<PathBuf, String> {
+    let size = size.clamp(MIN_SIZE, MAX_SIZE);
+    let (path_hash, _, key) = cache_key(source, size)?;
+
+    let _guard = LOCKS[(path_hash as usize) % LOCK_COUNT].lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(cached) = ["jpg", "png"].iter().map(|ext| cache_dir.join(format!("{}.{}", key, ext))).find(|p| p.is_file()) {
+        return Ok(cached);
+    }
+
+    let image = load_image(
+        source,
+        LoadOptions { apply_orientation: true },
+        TargetSize::Contain(size as f64, size as f64),
+    )?;
+    let image = if image.width() > size || image.height() > size {
+        image.thumbnail(size, size)
+    } else {
+        image
+    };
+
+    fs::create_dir_all(cache_dir).map_err(|e| e.to_string())?;
+    let ext = if image.color().has_alpha() { "png" } else { "jpg" };
+    let path = cache_dir.join(format!("{}.{}", key, ext));
+
+    // Write to a temporary file first so readers never see a partial thumbnail
+    let temp = cache_dir.join(format!("{}.{}.tmp", key, TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)));
+    let written = if ext == "png" {
+        image.to_rgba8().save_with_format(&temp, ImageFormat::Png).map_err(|e| e.to_string())
+    } else {
+        File::create(&temp)
+            .map_err(|e| e.to_string())
+            .and_then(|file| {
+                JpegEncoder::new_with_quality(BufWriter::new(file), JPEG_QUALITY)
+                    .encode_image(&image.to_rgb8())
+                    .map_err(|e| e.to_string())
+            })
+    };
+    if let Err(e) = written.and_then(|_| fs::rename(&temp, &path).map_err(|e| e.to_string())) {
+        let _ = fs::remove_file(&temp);
+        return Err(e);
+    }
+
+    Ok(path)
+}
+
+// Delete thumbnails of older versions of the given sources at `size`, reading the cache directory once
+pub fn remove_stale_thumbnails(cache_dir: &Path, sources: &[String], size: u32) {
+    let size = size.clamp(MIN_SIZE, MAX_SIZE);
+    let current: HashMap<String, String> = sources
+        .iter()
+        .filter_map(|source| cache_key(Path::new(source), size).ok())
+        .map(|(_, prefix, key)| (prefix, key))
+        .collect();
+
+    let Ok(entries) = fs::read_dir(cache_dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.ends_with(".tmp") {
+            continue;
+        }
+        let key = name.split('.').next().unwrap_or_default();
+        let Some(prefix) = key.rfind('-').map(|end| &key[..=end]) else {
+            continue;
+        };
+        if current.get(prefix).is_some_and(|current_key| current_key != key) {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+}
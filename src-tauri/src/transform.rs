@@ -0,0 +1,639 @@
+use crate::compose::Patch;
+use crate::shapes::rounded_rect_distance;
+use image::RgbaImage;
+
+// Downscaling is done in cheap 2x box steps until at most this many source pixels remain per drawn
+// pixel; the reconstruction filter then does the final step (same quality, far fewer filter taps)
+const MAX_FILTER_REDUCTION: f64 = 2.0;
+
+// Layers thinner than this (in pixels) can't reach half an 8-bit alpha step and are skipped;
+// resampling them would also widen the filter kernel without bound
+const MIN_VISIBLE_EXTENT: f64 = 1.0 / 512.0;
+
+// Reconstruction filter used when resampling a source image onto the canvas
+#[derive(Debug, Clone, Copy)]
+pub enum Filter {
+    CatmullRom,
+    Lanczos3,
+}
+
+impl Filter {
+    fn support(self) -> f64 {
+        match self {
+            Filter::CatmullRom => 2.0,
+            Filter::Lanczos3 => 3.0,
+        }
+    }
+
+    fn weight(self, x: f64) -> f64 {
+        let x = x.abs();
+        match self {
+            Filter::CatmullRom => {
+                if x < 1.0 {
+                    1.5 * x * x * x - 2.5 * x * x + 1.0
+                } else if x < 2.0 {
+                    -0.5 * x * x * x + 2.5 * x * x - 4.0 * x + 2.0
+                } else {
+                    0.0
+                }
+            }
+            Filter::Lanczos3 => {
+                if x < 1e-8 {
+                    1.0
+                } else if x < 3.0 {
+                    let px = std::f64::consts::PI * x;
+                    3.0 * px.sin() * (px / 3.0).sin() / (px * px)
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+// Layer box on the canvas: top-left corner, size, and rotation in degrees (clockwise) around the box center
+#[derive(Debug, Clone, Copy)]
+pub struct LayerFrame {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    pub rotation: f64,
+}
+
+impl LayerFrame {
+    pub fn new(x: f64, y: f64, width: f64, height: f64, rotation: f64) -> Self {
+        LayerFrame { x, y, width, height, rotation }
+    }
+
+    pub fn is_axis_aligned(&self) -> bool {
+        self.rotation.rem_euclid(360.0).abs() < 1e-9
+    }
+
+    // Map a point from layer-local coordinates (origin at the box top-left) to canvas coordinates
+    pub fn to_canvas(self, lx: f64, ly: f64) -> (f64, f64) {
+        let (sin, cos) = self.rotation.to_radians().sin_cos();
+        let dx = lx - self.width / 2.0;
+        let dy = ly - self.height / 2.0;
+        (
+            self.x + self.width / 2.0 + dx * cos - dy * sin,
+            self.y + self.height / 2.0 + dx * sin + dy * cos,
+        )
+    }
+
+    // Map a point from canvas coordinates back to layer-local coordinates
+    pub fn to_local(self, cx: f64, cy: f64) -> (f64, f64) {
+        let (sin, cos) = self.rotation.to_radians().sin_cos();
+        let dx = cx - self.x - self.width / 2.0;
+        let dy = cy - self.y - self.height / 2.0;
+        (
+            self.width / 2.0 + dx * cos + dy * sin,
+            self.height / 2.0 - dx * sin + dy * cos,
+        )
+    }
+
+    // Layer-local rectangle covering the whole canvas (nothing outside it can be drawn)
+    pub fn canvas_rect(&self, canvas_size: (u32, u32)) -> LocalRect {
+        let (width, height) = (canvas_size.0 as f64, canvas_size.1 as f64);
+        let corners = [
+            self.to_local(0.0, 0.0),
+            self.to_local(width, 0.0),
+            self.to_local(0.0, height),
+            self.to_local(width, height),
+        ];
+
+        let min_x = corners.iter().map(|c| c.0).fold(f64::INFINITY, f64::min);
+        let max_x = corners.iter().map(|c| c.0).fold(f64::NEG_INFINITY, f64::max);
+        let min_y = corners.iter().map(|c| c.1).fold(f64::INFINITY, f64::min);
+        let max_y = corners.iter().map(|c| c.1).fold(f64::NEG_INFINITY, f64::max);
+        LocalRect::new(min_x, min_y, max_x - min_x, max_y - min_y)
+    }
+
+    // Integer canvas bounds (x0, y0, x1, y1) covering `rect` after rotation, clipped to the canvas
+    pub fn canvas_bounds(&self, rect: &LocalRect, canvas_size: (u32, u32)) -> Option<(i64, i64, i64, i64)> {
+        let corners = [
+            self.to_canvas(rect.x, rect.y),
+            self.to_canvas(rect.x + rect.width, rect.y),
+            self.to_canvas(rect.x, rect.y + rect.height),
+            self.to_canvas(rect.x + rect.width, rect.y + rect.height),
+        ];
+
+        let min_x = corners.iter().map(|c| c.0).fold(f64::INFINITY, f64::min);
+        let max_x = corners.iter().map(|c| c.0).fold(f64::NEG_INFINITY, f64::max);
+        let min_y = corners.iter().map(|c| c.1).fold(f64::INFINITY, f64::min);
+        let max_y = corners.iter().map(|c| c.1).fold(f64::NEG_INFINITY, f64::max);
+
+        let x0 = (min_x.floor() as i64 - 1).max(0);
+        let y0 = (min_y.floor() as i64 - 1).max(0);
+        let x1 = (max_x.ceil() as i64 + 1).min(canvas_size.0 as i64);
+        let y1 = (max_y.ceil() as i64 + 1).min(canvas_size.1 as i64);
+
+        if x0 >= x1 || y0 >= y1 {
+            None
+        } else {
+            Some((x0, y0, x1, y1))
+        }
+    }
+}
+
+// Rectangle in layer-local coordinates
+#[derive(Debug, Clone, Copy)]
+pub struct LocalRect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl LocalRect {
+    pub fn new(x: f64, y: f64, width: f64, height: f64) -> Self {
+        LocalRect { x, y, width, height }
+    }
+
+    // Overlapping part of two rectangles (zero-sized when they do not overlap)
+    pub fn intersect(&self, other: &LocalRect) -> LocalRect {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let right = (self.x + self.width).min(other.x + other.width);
+        let bottom = (self.y + self.height).min(other.y + other.height);
+        LocalRect::new(x, y, (right - x).max(0.0), (bottom - y).max(0.0))
+    }
+
+    // Anti-aliased coverage of a unit pixel centered at (lx, ly) by this rectangle
+    pub fn coverage(&self, lx: f64, ly: f64) -> f32 {
+        let inside_x = (lx - self.x).min(self.x + self.width - lx);
+        let inside_y = (ly - self.y).min(self.y + self.height - ly);
+        let cx = (inside_x + 0.5).clamp(0.0, 1.0).min(self.width.max(0.0));
+        let cy = (inside_y + 0.5).clamp(0.0, 1.0).min(self.height.max(0.0));
+        (cx * cy) as f32
+    }
+
+    // Same as coverage, with the corners rounded by `corner_radius`
+    pub fn rounded_coverage(&self, corner_radius: f64, lx: f64, ly: f64) -> f32 {
+        if corner_radius <= 0.0 {
+            return self.coverage(lx, ly);
+        }
+        let d = rounded_rect_distance(self.width, self.height, corner_radius, lx - self.x, ly - self.y);
+        (0.5 - d).clamp(0.0, 1.0) as f32
+    }
+}
+
+// How an image is sized inside its layer box
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImageFit {
+    Contain, // Whole image visible, aspect ratio kept
+    Cover,   // Box filled, aspect ratio kept, overflow cropped
+    Stretch, // Box filled, aspect ratio ignored
+}
+
+impl ImageFit {
+    // Accepts the editor's scale modes ("fit", "fill") as well as the CSS names
+    pub fn parse(value: Option<&str>) -> Self {
+        match value {
+            Some("fill") | Some("cover") => ImageFit::Cover,
+            Some("stretch") => ImageFit::Stretch,
+            _ => ImageFit::Contain,
+        }
+    }
+}
+
+// Where a fitted image sits in its box
+#[derive(Debug, Clone, Copy)]
+pub struct Placement {
+    pub fit: ImageFit,
+    pub align_x: f64, // 0 = left, 0.5 = center, 1 = right
+    pub align_y: f64, // 0 = top, 0.5 = middle, 1 = bottom
+    pub focus: Option<(f64, f64)>, // Cover only: point of the image (0..1) kept as close to the box center as the crop allows
+}
+
+// Image rectangle in layer-local coordinates for a `source_width`×`source_height` image in a `width`×`height` box.
+// Cover rectangles overflow the box and have to be clipped to it.
+pub fn fit_rect(source_width: f64, source_height: f64, width: f64, height: f64, placement: &Placement) -> LocalRect {
+    if placement.fit == ImageFit::Stretch || source_width <= 0.0 || source_height <= 0.0 {
+        return LocalRect::new(0.0, 0.0, width, height);
+    }
+
+    let (scale_x, scale_y) = (width / source_width, height / source_height);
+    let scale = match placement.fit {
+        ImageFit::Cover => scale_x.max(scale_y),
+        _ => scale_x.min(scale_y),
+    };
+    let (fitted_width, fitted_height) = (source_width * scale, source_height * scale);
+
+    let (x, y) = match (placement.fit, placement.focus) {
+        (ImageFit::Cover, Some((focus_x, focus_y))) => (
+            (width / 2.0 - focus_x * fitted_width).clamp((width - fitted_width).min(0.0), 0.0),
+            (height / 2.0 - focus_y * fitted_height).clamp((height - fitted_height).min(0.0), 0.0),
+        ),
+        _ => (
+            (width - fitted_width) * placement.align_x,
+            (height - fitted_height) * placement.align_y,
+        ),
+    };
+    LocalRect::new(x, y, fitted_width, fitted_height)
+}
+
+// Compute filter taps along one axis for a sample at source coordinate `center`.
+// `scale` is destination pixels per source pixel; when downscaling the kernel is widened accordingly.
+// Returns the first (unclamped) source index; normalized weights are written to `weights`.
+fn compute_taps(filter: Filter, center: f64, scale: f64, weights: &mut Vec<f32>) -> i64 {
+    let filter_scale = scale.min(1.0);
+    let support = filter.support() / filter_scale;
+    let start = (center - support).floor() as i64;
+    let end = (center + support).ceil() as i64;
+
+    weights.clear();
+    let mut sum = 0.0;
+    for i in start..end {
+        let w = filter.weight((i as f64 + 0.5 - center) * filter_scale);
+        weights.push(w as f32);
+        sum += w;
+    }
+    if sum.abs() > 1e-12 {
+        let inv = (1.0 / sum) as f32;
+        weights.iter_mut().for_each(|w| *w *= inv);
+    }
+
+    start
+}
+
+// Fetch a source pixel as premultiplied floats, clamping coordinates to the image edge
+#[inline]
+fn fetch_premultiplied(source: &RgbaImage, x: i64, y: i64) -> [f32; 4] {
+    let (w, h) = source.dimensions();
+    let x = x.clamp(0, w as i64 - 1) as u32;
+    let y = y.clamp(0, h as i64 - 1) as u32;
+    let p = source.get_pixel(x, y);
+    let a = p[3] as f32 / 255.0;
+    [
+        p[0] as f32 / 255.0 * a,
+        p[1] as f32 / 255.0 * a,
+        p[2] as f32 / 255.0 * a,
+        a,
+    ]
+}
+
+#[inline]
+fn clamp_premultiplied(mut p: [f32; 4]) -> [f32; 4] {
+    p[3] = p[3].clamp(0.0, 1.0);
+    for c in 0..3 {
+        p[c] = p[c].clamp(0.0, p[3]);
+    }
+    p
+}
+
+// Resample `source` so that it covers `rect` inside the layer frame, resizing and rotating in one pass.
+// Edges are anti-aliased against the destination pixel grid and fractional positions are preserved.
+pub fn render_image(
+    source: &RgbaImage,
+    frame: &LayerFrame,
+    rect: LocalRect,
+    canvas_size: (u32, u32),
+) -> Option<Patch> {
+    render_image_clipped(source, frame, rect, rect, 0.0, canvas_size)
+}
+
+// Same as render_image, but only the part of `rect` inside `clip` is drawn (e.g. a cover-fitted image
+// overflowing its layer box), with the corners of the visible part rounded by `corner_radius`.
+// Clip edges are anti-aliased like the image edges.
+pub fn render_image_clipped(
+    source: &RgbaImage,
+    frame: &LayerFrame,
+    rect: LocalRect,
+    clip: LocalRect,
+    corner_radius: f64,
+    canvas_size: (u32, u32),
+) -> Option<Patch> {
+    let visible = rect.intersect(&clip);
+    if source.width() == 0
+        || source.height() == 0
+        || !(visible.width >= MIN_VISIBLE_EXTENT && visible.height >= MIN_VISIBLE_EXTENT)
+    {
+        return None;
+    }
+
+    let reduced = prescale(source, &rect);
+    let source = reduced.as_ref().unwrap_or(source);
+
+    if frame.is_axis_aligned() {
+        render_axis_aligned(source, frame, rect, visible, corner_radius, canvas_size)
+    } else {
+        render_rotated(source, frame, rect, visible, corner_radius, canvas_size)
+    }
+}
+
+// Helper: Halve the source along each axis while it stays MAX_FILTER_REDUCTION times larger than drawn.
+// An axis stops at 1px, so slivers and zero-sized rects can't keep the loop going.
+fn prescale(source: &RgbaImage, rect: &LocalRect) -> Option<RgbaImage> {
+    if !(rect.width.is_finite() && rect.height.is_finite()) || rect.width <= 0.0 || rect.height <= 0.0 {
+        return None;
+    }
+
+    let mut reduced: Option<RgbaImage> = None;
+    loop {
+        let current = reduced.as_ref().unwrap_or(source);
+        let halve_x = current.width() > 1 && current.width() as f64 / 2.0 >= rect.width * MAX_FILTER_REDUCTION;
+        let halve_y = current.height() > 1 && current.height() as f64 / 2.0 >= rect.height * MAX_FILTER_REDUCTION;
+        if !halve_x && !halve_y {
+            return reduced;
+        }
+        reduced = Some(halve(current, halve_x, halve_y));
+    }
+}
+
+// Helper: 2x box downsample (per axis), averaged with premultiplied alpha so transparent pixels don't bleed
+fn halve(source: &RgbaImage, halve_x: bool, halve_y: bool) -> RgbaImage {
+    let (src_w, src_h) = (source.width() as usize, source.height() as usize);
+    let step_x = if halve_x { 2 } else { 1 };
+    let step_y = if halve_y { 2 } else { 1 };
+    let out_w = src_w.div_ceil(step_x);
+    let out_h = src_h.div_ceil(step_y);
+    let raw: &[u8] = source.as_raw();
+
+    let mut out = vec![0u8; out_w * out_h * 4];
+    let mut sums = vec![[0u32; 5]; out_w]; // Premultiplied RGB, alpha, pixel count
+    for (y, out_row) in out.chunks_exact_mut(out_w * 4).enumerate() {
+        sums.iter_mut().for_each(|s| *s = [0; 5]);
+        for sy in (y * step_y)..(y * step_y + step_y).min(src_h) {
+            let row = &raw[sy * src_w * 4..][..src_w * 4];
+            for (sum, block) in sums.iter_mut().zip(row.chunks(step_x * 4)) {
+                for p in block.chunks_exact(4) {
+                    let a = p[3] as u32;
+                    sum[0] += p[0] as u32 * a;
+                    sum[1] += p[1] as u32 * a;
+                    sum[2] += p[2] as u32 * a;
+                    sum[3] += a;
+                    sum[4] += 1;
+                }
+            }
+        }
+        // Fully transparent blocks stay zero
+        for (pixel, sum) in out_row.chunks_exact_mut(4).zip(sums.iter()).filter(|(_, sum)| sum[3] > 0) {
+            for c in 0..3 {
+                pixel[c] = ((sum[c] + sum[3] / 2) / sum[3]) as u8;
+            }
+            pixel[3] = ((sum[3] + sum[4] / 2) / sum[4]) as u8;
+        }
+    }
+
+    RgbaImage::from_raw(out_w as u32, out_h as u32, out).expect("buffer matches dimensions")
+}
+
+// Separable two-pass path for unrotated layers (Lanczos3)
+fn render_axis_aligned(
+    source: &RgbaImage,
+    frame: &LayerFrame,
+    rect: LocalRect,
+    visible: LocalRect,
+    corner_radius: f64,
+    canvas_size: (u32, u32),
+) -> Option<Patch> {
+    let filter = Filter::Lanczos3;
+    let (x0, y0, x1, y1) = frame.canvas_bounds(&visible, canvas_size)?;
+    let out_w = (x1 - x0) as usize;
+    let out_h = (y1 - y0) as usize;
+
+    let (src_w, src_h) = source.dimensions();
+    let scale_x = rect.width / src_w as f64;
+    let scale_y = rect.height / src_h as f64;
+    let origin_x = frame.x + rect.x;
+    let origin_y = frame.y + rect.y;
+
+    // Horizontal taps for each destination column
+    let mut weights = Vec::new();
+    let columns: Vec<(i64, Vec<f32>)> = (x0..x1)
+        .map(|px| {
+            let u = (px as f64 + 0.5 - origin_x) / scale_x;
+            let start = compute_taps(filter, u, scale_x, &mut weights);
+            (start, weights.clone())
+        })
+        .collect();
+
+    // Vertical taps for each destination row
+    let rows: Vec<(i64, Vec<f32>)> = (y0..y1)
+        .map(|py| {
+            let v = (py as f64 + 0.5 - origin_y) / scale_y;
+            let start = compute_taps(filter, v, scale_y, &mut weights);
+            (start, weights.clone())
+        })
+        .collect();
+
+    // Source rows touched by the vertical pass
+    let row_min = rows.iter().map(|(s, _)| *s).min()?.clamp(0, src_h as i64 - 1);
+    let row_max = rows
+        .iter()
+        .map(|(s, w)| s + w.len() as i64 - 1)
+        .max()?
+        .clamp(0, src_h as i64 - 1);
+
+    // Pass 1: resample touched source rows horizontally
+    let span = (row_max - row_min + 1) as usize;
+    let mut intermediate = vec![[0.0f32; 4]; span * out_w];
+    for sy in row_min..=row_max {
+        let row = &mut intermediate[(sy - row_min) as usize * out_w..][..out_w];
+        for (out, (start, ws)) in row.iter_mut().zip(columns.iter()) {
+            let mut acc = [0.0f32; 4];
+            for (k, w) in ws.iter().enumerate() {
+                let p = fetch_premultiplied(source, start + k as i64, sy);
+                for c in 0..4 {
+                    acc[c] += p[c] * w;
+                }
+            }
+            *out = acc;
+        }
+    }
+
+    // Pass 2: resample vertically and apply edge coverage
+    let mut data = vec![[0.0f32; 4]; out_w * out_h];
+    for (ry, (start, ws)) in rows.iter().enumerate() {
+        let ly = (y0 + ry as i64) as f64 + 0.5 - frame.y;
+        for rx in 0..out_w {
+            let lx = (x0 + rx as i64) as f64 + 0.5 - frame.x;
+            let coverage = visible.rounded_coverage(corner_radius, lx, ly);
+            if coverage <= 0.0 {
+                continue;
+            }
+
+            let mut acc = [0.0f32; 4];
+            for (k, w) in ws.iter().enumerate() {
+                let sy = (start + k as i64).clamp(row_min, row_max);
+                let p = intermediate[(sy - row_min) as usize * out_w + rx];
+                for c in 0..4 {
+                    acc[c] += p[c] * w;
+                }
+            }
+
+            let acc = clamp_premultiplied(acc);
+            data[ry * out_w + rx] = acc.map(|v| v * coverage);
+        }
+    }
+
+    Some(Patch::from_premultiplied(x0, y0, out_w as u32, out_h as u32, &data))
+}
+
+// General path for rotated layers: inverse-map every destination pixel into the source (Catmull-Rom)
+fn render_rotated(
+    source: &RgbaImage,
+    frame: &LayerFrame,
+    rect: LocalRect,
+    visible: LocalRect,
+    corner_radius: f64,
+    canvas_size: (u32, u32),
+) -> Option<Patch> {
+    let filter = Filter::CatmullRom;
+    let (x0, y0, x1, y1) = frame.canvas_bounds(&visible, canvas_size)?;
+    let out_w = (x1 - x0) as usize;
+    let out_h = (y1 - y0) as usize;
+
+    let (src_w, src_h) = source.dimensions();
+    let scale_x = rect.width / src_w as f64;
+    let scale_y = rect.height / src_h as f64;
+
+    let mut weights_x = Vec::new();
+    let mut weights_y = Vec::new();
+    let mut data = vec![[0.0f32; 4]; out_w * out_h];
+
+    for ry in 0..out_h {
+        for rx in 0..out_w {
+            let cx = (x0 + rx as i64) as f64 + 0.5;
+            let cy = (y0 + ry as i64) as f64 + 0.5;
+            let (lx, ly) = frame.to_local(cx, cy);
+
+            let coverage = visible.rounded_coverage(corner_radius, lx, ly);
+            if coverage <= 0.0 {
+                continue;
+            }
+
+            let u = (lx - rect.x) / scale_x;
+            let v = (ly - rect.y) / scale_y;
+            let start_x = compute_taps(filter, u, scale_x, &mut weights_x);
+            let start_y = compute_taps(filter, v, scale_y, &mut weights_y);
+
+            let mut acc = [0.0f32; 4];
+            for (j, wy) in weights_y.iter().enumerate() {
+                let sy = start_y + j as i64;
+                for (i, wx) in weights_x.iter().enumerate() {
+                    let p = fetch_premultiplied(source, start_x + i as i64, sy);
+                    let w = wx * wy;
+                    for c in 0..4 {
+                        acc[c] += p[c] * w;
+                    }
+                }
+            }
+
+            let acc = clamp_premultiplied(acc);
+            data[ry * out_w + rx] = acc.map(|v| v * coverage);
+        }
+    }
+
+    Some(Patch::from_premultiplied(x0, y0, out_w as u32, out_h as u32, &data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prescale_stops_at_one_pixel_for_slivers() {
+        let source = RgbaImage::from_pixel(64, 64, image::Rgba([255, 0, 0, 255]));
+        let reduced = prescale(&source, &LocalRect::new(0.0, 0.0, 1e-6, 32.0)).expect("width is reduced");
+        assert_eq!(reduced.dimensions(), (1, 64));
+    }
+
+    #[test]
+    fn prescale_ignores_degenerate_rects() {
+        let source = RgbaImage::new(8, 8);
+        assert!(prescale(&source, &LocalRect::new(0.0, 0.0, 0.0, 4.0)).is_none());
+        assert!(prescale(&source, &LocalRect::new(0.0, 0.0, f64::NAN, 4.0)).is_none());
+        assert!(prescale(&source, &LocalRect::new(0.0, 0.0, f64::INFINITY, 4.0)).is_none());
+    }
+
+    #[test]
+    fn near_zero_size_layer_is_skipped() {
+        let source = RgbaImage::from_pixel(16, 16, image::Rgba([0, 0, 255, 255]));
+        for rotation in [0.0, 30.0] {
+            let frame = LayerFrame::new(10.0, 10.0, 1e-9, 1e-9, rotation);
+            let rect = LocalRect::new(0.0, 0.0, 1e-9, 1e-9);
+            assert!(render_image(&source, &frame, rect, (32, 32)).is_none());
+        }
+
+        let frame = LayerFrame::new(10.0, 10.0, 0.01, 8.0, 0.0);
+        assert!(render_image(&source, &frame, LocalRect::new(0.0, 0.0, 0.01, 8.0), (32, 32)).is_some());
+    }
+
+    // Helper: Deterministic test image with distinct colors and alpha in every pixel
+    fn pattern(width: u32, height: u32) -> RgbaImage {
+        RgbaImage::from_fn(width, height, |x, y| {
+            image::Rgba([
+                (x * 37 + y * 11) as u8,
+                (x * 5 + y * 53) as u8,
+                ((x ^ y) * 29) as u8,
+                (128 + (x * 7 + y * 3) % 128) as u8,
+            ])
+        })
+    }
+
+    // Helper: FNV-1a over a patch's position, size and pixels (golden values pin the resampler output)
+    fn checksum(patch: &Patch) -> u64 {
+        let header = [patch.x, patch.y, patch.pixels.width() as i64, patch.pixels.height() as i64];
+        header
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .chain(patch.pixels.as_raw().iter().copied())
+            .fold(0xcbf29ce484222325, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3))
+    }
+
+    #[test]
+    fn lanczos_at_native_size_copies_the_source() {
+        let source = pattern(8, 6);
+        let frame = LayerFrame::new(2.0, 3.0, 8.0, 6.0, 0.0);
+        let patch = render_image(&source, &frame, LocalRect::new(0.0, 0.0, 8.0, 6.0), (16, 16)).unwrap();
+        for (x, y, pixel) in patch.pixels.enumerate_pixels() {
+            let (sx, sy) = (patch.x + x as i64 - 2, patch.y + y as i64 - 3);
+            let expected = if (0..8).contains(&sx) && (0..6).contains(&sy) {
+                *source.get_pixel(sx as u32, sy as u32)
+            } else {
+                image::Rgba([0, 0, 0, 0])
+            };
+            assert_eq!(*pixel, expected, "at {}, {}", sx, sy);
+        }
+    }
+
+    #[test]
+    fn lanczos_axis_aligned_matches_golden() {
+        let source = pattern(23, 17);
+        let frame = LayerFrame::new(1.25, 0.5, 9.5, 7.0, 0.0);
+        let patch = render_image(&source, &frame, LocalRect::new(0.0, 0.0, 9.5, 7.0), (16, 16)).unwrap();
+        assert_eq!(checksum(&patch), 8671643509673050009);
+    }
+
+    #[test]
+    fn catmull_rom_rotated_matches_golden() {
+        let source = pattern(20, 14);
+        let frame = LayerFrame::new(4.0, 3.0, 12.0, 8.0, 30.0);
+        let patch = render_image(&source, &frame, LocalRect::new(0.0, 0.0, 12.0, 8.0), (24, 24)).unwrap();
+        assert_eq!(checksum(&patch), 14842261470901788111);
+    }
+
+    #[test]
+    fn halving_averages_with_premultiplied_alpha() {
+        let source = RgbaImage::from_vec(
+            2,
+            2,
+            vec![255, 0, 0, 255, 0, 255, 0, 0, 0, 0, 255, 128, 0, 255, 0, 255],
+        )
+        .unwrap();
+        // The transparent green pixel adds no color, only its share of alpha
+        assert_eq!(halve(&source, true, true).as_raw(), &vec![102, 102, 51, 160]);
+        assert_eq!(halve(&source, true, false).as_raw(), &vec![255, 0, 0, 128, 0, 170, 85, 192]);
+    }
+
+    #[test]
+    fn box_prescale_matches_golden() {
+        let source = pattern(64, 48);
+        let reduced = prescale(&source, &LocalRect::new(0.0, 0.0, 5.0, 20.0)).expect("source is reduced");
+        assert_eq!(reduced.dimensions(), (16, 48));
+        assert_eq!(checksum(&Patch { x: 0, y: 0, pixels: reduced }), 3019779940409724688);
+    }
+}
+
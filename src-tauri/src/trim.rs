@@ -0,0 +1,68 @@
+use crate::keying::border_color;
+use image::{Rgba, RgbaImage};
+
+const MIN_CONTENT_ALPHA: u8 = 8; // Nearly invisible pixels (shadows faded out, compression noise) do not count as content
+
+// Result of trimming: the new image and where its top-left corner lies in the original (may be negative with padding)
+pub struct Trimmed {
+    pub image: RgbaImage,
+    pub origin: (i64, i64),
+}
+
+// Crop the image to its content and surround it with `padding` (fraction of the content's larger side).
+// Content is whatever is not transparent and, when the border has a dominant color, differs from it by more
+// than `tolerance` (0..1 of the channel range). Padding uses that color, or transparency.
+// Returns None when there is nothing to trim (no content found, or the content already fills the image).
+pub fn trim_to_content(image: &RgbaImage, tolerance: f64, padding: f64) -> Option<Trimmed> {
+    let background = border_color(image);
+    let (x0, y0, x1, y1) = content_bounds(image, background, tolerance)?;
+
+    let pad = ((x1 - x0).max(y1 - y0) as f64 * padding.max(0.0)).round() as i64;
+    let (left, top) = (x0 as i64 - pad, y0 as i64 - pad);
+    let width = (x1 - x0) as i64 + 2 * pad;
+    let height = (y1 - y0) as i64 + 2 * pad;
+    if left == 0 && top == 0 && width == image.width() as i64 && height == image.height() as i64 {
+        return None;
+    }
+
+    let fill = match background {
+        Some(color) => Rgba([color[0].round() as u8, color[1].round() as u8, color[2].round() as u8, 255]),
+        None => Rgba([0, 0, 0, 0]),
+    };
+    let mut pixels = fill.0.repeat(width as usize * height as usize);
+    let stride = image.width() as usize * 4;
+    for y in y0..y1 {
+        let source = &image.as_raw()[y as usize * stride + x0 as usize * 4..][..(x1 - x0) as usize * 4];
+        let start = ((y as i64 - top) * width + pad) as usize * 4;
+        pixels[start..start + source.len()].copy_from_slice(source);
+    }
+
+    Some(Trimmed {
+        image: RgbaImage::from_raw(width as u32, height as u32, pixels).expect("buffer matches dimensions"),
+        origin: (left, top),
+    })
+}
+
+// Helper: Bounds (x0, y0, x1, y1; exclusive end) of the content pixels, None when there are none
+fn content_bounds(image: &RgbaImage, background: Option<[f64; 3]>, tolerance: f64) -> Option<(u32, u32, u32, u32)> {
+    let limit = tolerance.clamp(0.0, 1.0) * 255.0;
+    let is_content = |p: &[u8]| {
+        p[3] >= MIN_CONTENT_ALPHA
+            && match background {
+                Some(color) => (0..3).any(|c| (p[c] as f64 - color[c]).abs() > limit),
+                None => true,
+            }
+    };
+
+    let (mut x0, mut y0, mut x1, mut y1) = (u32::MAX, u32::MAX, 0, 0);
+    for (y, row) in image.as_raw().chunks_exact(image.width() as usize * 4).enumerate() {
+        let (Some(first), Some(last)) = (row.chunks_exact(4).position(is_content), row.chunks_exact(4).rposition(is_content)) else {
+            continue;
+        };
+        x0 = x0.min(first as u32);
+        x1 = x1.max(last as u32 + 1);
+        y0 = y0.min(y as u32);
+        y1 = y as u32 + 1;
+    }
+    (x0 < x1).then_some((x0, y0, x1, y1))
+}
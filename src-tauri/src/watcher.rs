@@ -0,0 +1,254 @@
+use crate::scan::{is_app_path, ScanOptions, Scanner};
+use crate::{output_relative_path, template_names, ImageFileInfo, OUTPUT_DIR};
+use notify_debouncer_mini::notify::{RecommendedWatcher, RecursiveMode};
+use notify_debouncer_mini::{new_debouncer, DebounceEventResult, Debouncer};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+
+const DEBOUNCE: Duration = Duration::from_millis(500); // Copying a batch of photos settles into one event
+
+// Payload of the `images-changed` event
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct ImagesChanged {
+    pub added: Vec<ImageFileInfo>,
+    pub removed: Vec<String>, // Paths
+    pub changed: Vec<ImageFileInfo>,
+    pub stale_outputs: HashMap<String, Vec<String>>, // Changed image path -> outputs generated before the change
+}
+
+impl ImagesChanged {
+    fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+// Keeps the working directory watched until dropped
+pub struct DirectoryWatcher {
+    _debouncer: Debouncer<RecommendedWatcher>,
+}
+
+// Watch the working directory and report image list changes (same scan options as the initial list).
+// `initial` is the list the changes are relative to; the directory is scanned when it is not given.
+// Afterwards only the paths reported by each event are scanned again.
+pub fn watch_directory<F>(
+    root: PathBuf,
+    options: ScanOptions,
+    initial: Option<Vec<ImageFileInfo>>,
+    on_change: F,
+) -> Result<DirectoryWatcher, String>
+where
+    F: Fn(ImagesChanged) + Send + 'static,
+{
+    let scanner = Scanner::new(&root, &options)?;
+    let initial = match initial {
+        Some(images) => images,
+        None => scanner.scan(&root)?,
+    };
+    let mut snapshot = index_by_path(initial);
+    let watch_root = root.clone();
+    let mode = if options.recursive {
+        RecursiveMode::Recursive
+    } else {
+        RecursiveMode::NonRecursive
+    };
+
+    let mut debouncer = new_debouncer(DEBOUNCE, move |result: DebounceEventResult| {
+        let Ok(events) = result else {
+            return;
+        };
+
+        // Outputs, thumbnails and templates are written by the app itself
+        let mut updates: HashMap<String, Option<ImageFileInfo>> = HashMap::new();
+        for event in events.iter().filter(|event| !is_app_path(&root, &event.path)) {
+            rescan_path(&scanner, &snapshot, &event.path, &mut updates);
+        }
+
+        let changes = apply_updates(&root, &mut snapshot, updates);
+        if !changes.is_empty() {
+            on_change(changes);
+        }
+    })
+    .map_err(|e| e.to_string())?;
+
+    debouncer.watcher().watch(&watch_root, mode).map_err(|e| e.to_string())?;
+
+    Ok(DirectoryWatcher {
+        _debouncer: debouncer,
+    })
+}
+
+// Helper: Scan results keyed by absolute path
+fn index_by_path(images: Vec<ImageFileInfo>) -> HashMap<String, ImageFileInfo> {
+    images.into_iter().map(|image| (image.path.clone(), image)).collect()
+}
+
+// Helper: Current state of the images at or inside a changed path (None = no longer in the list)
+fn rescan_path(
+    scanner: &Scanner,
+    snapshot: &HashMap<String, ImageFileInfo>,
+    path: &Path,
+    updates: &mut HashMap<String, Option<ImageFileInfo>>,
+) {
+    let key = path.to_string_lossy().to_string();
+    if path.is_dir() {
+        // A folder was added, moved or renamed: its images are those found in it now
+        for known in snapshot.keys().filter(|known| Path::new(known).starts_with(path)) {
+            updates.entry(known.clone()).or_insert(None);
+        }
+        for image in scanner.scan(path).unwrap_or_default() {
+            updates.insert(image.path.clone(), Some(image));
+        }
+    } else if path.exists() || snapshot.contains_key(&key) {
+        updates.insert(key, scanner.image(path));
+    } else {
+        // A removed folder (or a file that was never listed)
+        for known in snapshot.keys().filter(|known| Path::new(known).starts_with(path)) {
+            updates.insert(known.clone(), None);
+        }
+    }
+}
+
+// Helper: Apply the new state of changed paths to the snapshot and report the differences.
+// An image changed when its size or modification time differs.
+fn apply_updates(
+    root: &Path,
+    snapshot: &mut HashMap<String, ImageFileInfo>,
+    updates: HashMap<String, Option<ImageFileInfo>>,
+) -> ImagesChanged {
+    let mut changes = ImagesChanged::default();
+    let mut templates: Option<Vec<String>> = None; // Read when the first changed image needs it
+
+    for (path, image) in updates {
+        match (snapshot.get(&path), image) {
+            (None, Some(image)) => {
+                changes.added.push(image.clone());
+                snapshot.insert(path, image);
+            }
+            (Some(old), Some(image))
+                if old.metadata.modified != image.metadata.modified
+                    || old.metadata.file_size != image.metadata.file_size =>
+            {
+                let templates = templates.get_or_insert_with(|| template_names(root));
+                let outputs = stale_outputs(root, templates, &image);
+                if !outputs.is_empty() {
+                    changes.stale_outputs.insert(path.clone(), outputs);
+                }
+                changes.changed.push(image.clone());
+                snapshot.insert(path, image);
+            }
+            (Some(_), None) => {
+                snapshot.remove(&path);
+                changes.removed.push(path);
+            }
+            _ => {}
+        }
+    }
+
+    changes.added.sort_by_key(|image| image.relative_path.to_lowercase());
+    changes.changed.sort_by_key(|image| image.relative_path.to_lowercase());
+    changes.removed.sort();
+    changes
+}
+
+// Helper: Generated outputs of an image older than the image itself.
+// Outputs go to `infographics/` or `infographics/<template>/` for each saved template.
+fn stale_outputs(root: &Path, templates: &[String], image: &ImageFileInfo) -> Vec<String> {
+    let output_dir = root.join(OUTPUT_DIR);
+    let relative = output_relative_path(image);
+    let candidates = std::iter::once(output_dir.join(&relative))
+        .chain(templates.iter().map(|template| output_dir.join(template).join(&relative)));
+
+    let source_modified = image.metadata.modified.unwrap_or(0);
+    candidates
+        .filter(|output| {
+            fs::metadata(output)
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map(|time| (time.as_millis() as u64) < source_modified)
+                .unwrap_or(false)
+        })
+        .map(|output| output.to_string_lossy().to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Helper: Empty folder for a test under the system temp directory
+    fn temp_root(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("infographics-watcher-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        root
+    }
+
+    fn write_image(path: &Path) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        image::RgbaImage::new(2, 2).save(path).unwrap();
+    }
+
+    fn rescan(scanner: &Scanner, root: &Path, snapshot: &mut HashMap<String, ImageFileInfo>, paths: &[PathBuf]) -> ImagesChanged {
+        let mut updates = HashMap::new();
+        for path in paths {
+            rescan_path(scanner, snapshot, path, &mut updates);
+        }
+        apply_updates(root, snapshot, updates)
+    }
+
+    #[test]
+    fn rescans_changed_files_and_folders() {
+        let root = temp_root("rescan");
+        write_image(&root.join("a.png"));
+        write_image(&root.join("sub/b.png"));
+        let options = ScanOptions {
+            recursive: true,
+            ..ScanOptions::default()
+        };
+        let scanner = Scanner::new(&root, &options).unwrap();
+        let mut snapshot = index_by_path(scanner.scan(&root).unwrap());
+        assert_eq!(snapshot.len(), 2);
+
+        write_image(&root.join("sub/c.png"));
+        fs::remove_file(root.join("a.png")).unwrap();
+        let changes = rescan(&scanner, &root, &mut snapshot, &[root.join("sub/c.png"), root.join("a.png")]);
+        assert_eq!(changes.added.iter().map(|i| i.relative_path.as_str()).collect::<Vec<_>>(), ["sub/c.png"]);
+        assert_eq!(changes.removed, [root.join("a.png").to_string_lossy()]);
+
+        fs::rename(root.join("sub"), root.join("moved")).unwrap();
+        let changes = rescan(&scanner, &root, &mut snapshot, &[root.join("sub"), root.join("moved")]);
+        assert_eq!(changes.removed.len(), 2);
+        assert_eq!(changes.added.iter().map(|i| i.relative_path.as_str()).collect::<Vec<_>>(), ["moved/b.png", "moved/c.png"]);
+        assert_eq!(snapshot.len(), 2);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn stale_outputs_only_checks_template_folders() {
+        let root = temp_root("stale");
+        let source = root.join("a.png");
+        write_image(&source);
+        for folder in ["infographics", "infographics/Sale", "infographics/archive"] {
+            write_image(&root.join(folder).join("a_infographic.png"));
+        }
+
+        let scanner = Scanner::new(&root, &ScanOptions::default()).unwrap();
+        let mut image = scanner.image(&source).unwrap();
+        image.metadata.modified = image.metadata.modified.map(|modified| modified + 60_000);
+
+        let mut outputs = stale_outputs(&root, &["Sale".to_string()], &image);
+        outputs.sort();
+        let expected: Vec<String> = ["infographics/Sale/a_infographic.png", "infographics/a_infographic.png"]
+            .iter()
+            .map(|relative| root.join(relative).to_string_lossy().to_string())
+            .collect();
+        assert_eq!(outputs, expected);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,649 @@
+// WOFF2 decoding: decompresses the table data and rebuilds transformed glyf/loca/hmtx tables,
+// producing a plain TrueType/OpenType font that the shaper and rasterizer can read.
+use std::io::Read;
+
+const WOFF2_SIGNATURE: u32 = 0x774F_4632; // "wOF2"
+const COLLECTION_FLAVOR: u32 = 0x7474_6366; // "ttcf"
+
+// Tags addressed by index in the table directory
+const KNOWN_TAGS: [&[u8; 4]; 63] = [
+    b"cmap", b"head", b"hhea", b"hmtx", b"maxp", b"name", b"OS/2", b"post", b"cvt ", b"fpgm", b"glyf",
+    b"loca", b"prep", b"CFF ", b"VORG", b"EBDT", b"EBLC", b"gasp", b"hdmx", b"kern", b"LTSH", b"PCLT",
+    b"VDMX", b"vhea", b"vmtx", b"BASE", b"GDEF", b"GPOS", b"GSUB", b"EBSC", b"JSTF", b"MATH", b"CBDT",
+    b"CBLC", b"COLR", b"CPAL", b"SVG ", b"sbix", b"acnt", b"avar", b"bdat", b"bloc", b"bsln", b"cvar",
+    b"fdsc", b"feat", b"fmtx", b"fvar", b"gvar", b"hsty", b"just", b"lcar", b"mort", b"morx", b"opbd",
+    b"prop", b"trak", b"Zapf", b"Silf", b"Glat", b"Gloc", b"Feat", b"Sill",
+];
+
+// Simple glyph flags (TrueType glyf table)
+const ON_CURVE: u8 = 0x01;
+const X_SHORT: u8 = 0x02;
+const Y_SHORT: u8 = 0x04;
+const X_SAME_OR_POSITIVE: u8 = 0x10;
+const Y_SAME_OR_POSITIVE: u8 = 0x20;
+
+// Composite glyph flags
+const ARG_1_AND_2_ARE_WORDS: u16 = 0x0001;
+const WE_HAVE_A_SCALE: u16 = 0x0008;
+const MORE_COMPONENTS: u16 = 0x0020;
+const WE_HAVE_AN_X_AND_Y_SCALE: u16 = 0x0040;
+const WE_HAVE_A_TWO_BY_TWO: u16 = 0x0080;
+const WE_HAVE_INSTRUCTIONS: u16 = 0x0100;
+
+pub fn is_woff2(data: &[u8]) -> bool {
+    data.len() >= 4 && u32::from_be_bytes([data[0], data[1], data[2], data[3]]) == WOFF2_SIGNATURE
+}
+
+struct TableEntry {
+    tag: [u8; 4],
+    transformed: bool,
+    data_length: usize, // Length inside the decompressed stream
+}
+
+// Convert a WOFF2 file into an SFNT (TrueType/OpenType) font
+pub fn decode(data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut reader = Reader::new(data);
+    if reader.u32()? != WOFF2_SIGNATURE {
+        return Err("Not a WOFF2 file".to_string());
+    }
+    let flavor = reader.u32()?;
+    if flavor == COLLECTION_FLAVOR {
+        return Err("WOFF2 font collections are not supported".to_string());
+    }
+    reader.skip(4)?; // length
+    let num_tables = reader.u16()? as usize;
+    reader.skip(6)?; // reserved, totalSfntSize
+    let compressed_size = reader.u32()? as usize;
+    reader.skip(24)?; // version, metadata and private blocks
+
+    let mut tables = Vec::with_capacity(num_tables);
+    for _ in 0..num_tables {
+        let flags = reader.u8()?;
+        let tag = match flags & 0x3F {
+            63 => reader.tag()?,
+            index => *KNOWN_TAGS[index as usize],
+        };
+        let version = flags >> 6;
+        // glyf and loca are transformed with version 0; other tables with any non-zero version
+        let transformed = if &tag == b"glyf" || &tag == b"loca" { version == 0 } else { version != 0 };
+        let orig_length = reader.base128()? as usize;
+        let data_length = if transformed { reader.base128()? as usize } else { orig_length };
+        tables.push(TableEntry { tag, transformed, data_length });
+    }
+
+    let compressed = reader.bytes(compressed_size)?;
+    let mut stream = Vec::new();
+    brotli_decompressor::Decompressor::new(compressed, 4096)
+        .read_to_end(&mut stream)
+        .map_err(|e| format!("Failed to decompress WOFF2 data: {}", e))?;
+
+    // Slice the decompressed stream into tables
+    let mut offset = 0;
+    let mut raw: Vec<&[u8]> = Vec::with_capacity(tables.len());
+    for table in &tables {
+        let end = offset + table.data_length;
+        raw.push(stream.get(offset..end).ok_or("Truncated WOFF2 table data")?);
+        offset = end;
+    }
+
+    let find = |tag: &[u8; 4]| tables.iter().position(|t| &t.tag == tag);
+    let mut output: Vec<([u8; 4], Vec<u8>)> = Vec::with_capacity(tables.len());
+    let mut glyph_x_mins: Option<Vec<i16>> = None;
+    let mut long_loca = false;
+
+    for (i, table) in tables.iter().enumerate() {
+        let data = match &table.tag {
+            b"glyf" if table.transformed => {
+                let glyphs = rebuild_glyf(raw[i])?;
+                long_loca = glyphs.long_loca;
+                output.push((*b"loca", glyphs.loca));
+                glyph_x_mins = Some(glyphs.x_mins);
+                glyphs.glyf
+            }
+            b"loca" if table.transformed => continue, // Rebuilt together with glyf
+            b"hmtx" if table.transformed => {
+                let x_mins = glyph_x_mins.as_deref().ok_or("hmtx transform requires a glyf table")?;
+                let hhea = find(b"hhea").map(|h| raw[h]).ok_or("Missing hhea table")?;
+                let num_h_metrics = Reader::new(hhea.get(34..).ok_or("Invalid hhea table")?).u16()? as usize;
+                rebuild_hmtx(raw[i], x_mins, num_h_metrics)?
+            }
+            _ if table.transformed => {
+                return Err(format!("Unsupported WOFF2 transform for {}", String::from_utf8_lossy(&table.tag)))
+            }
+            _ => raw[i].to_vec(),
+        };
+        output.push((table.tag, data));
+    }
+
+    // The rebuilt glyf may need long loca offsets where the original used short ones
+    if long_loca {
+        let head = output.iter_mut().find(|(tag, _)| tag == b"head").ok_or("Missing head table")?;
+        head.1.get_mut(50..52).ok_or("Invalid head table")?.copy_from_slice(&1u16.to_be_bytes()); // indexToLocFormat
+    }
+
+    Ok(build_sfnt(flavor, output))
+}
+
+struct RebuiltGlyphs {
+    glyf: Vec<u8>,
+    loca: Vec<u8>,
+    long_loca: bool,  // 32-bit loca offsets (head.indexToLocFormat = 1)
+    x_mins: Vec<i16>, // Per glyph, for derived left side bearings
+}
+
+// Helper: Rebuild glyf and loca from the WOFF2 transformed glyf table
+fn rebuild_glyf(data: &[u8]) -> Result<RebuiltGlyphs, String> {
+    let mut header = Reader::new(data);
+    header.skip(2)?; // reserved
+    header.skip(2)?; // optionFlags (overlap hints are not needed for rendering)
+    let num_glyphs = header.u16()? as usize;
+    let index_format = header.u16()?;
+
+    let mut sizes = [0usize; 7];
+    for size in sizes.iter_mut() {
+        *size = header.u32()? as usize;
+    }
+    let mut streams = Vec::with_capacity(7);
+    let mut offset = header.position();
+    for size in sizes {
+        streams.push(Reader::new(data.get(offset..offset + size).ok_or("Truncated glyf streams")?));
+        offset += size;
+    }
+
+    let [mut n_contours, mut n_points, mut flags, mut glyph_stream, mut composite, mut bbox, mut instructions]: [Reader; 7] =
+        streams.try_into().map_err(|_| "Invalid glyf streams")?;
+
+    let bitmap_length = 4 * num_glyphs.div_ceil(32);
+    let bbox_bitmap = bbox.bytes(bitmap_length)?;
+    let has_bbox = |glyph: usize| bbox_bitmap[glyph >> 3] & (0x80 >> (glyph & 7)) != 0;
+
+    let mut glyf: Vec<u8> = Vec::new();
+    let mut offsets: Vec<usize> = Vec::with_capacity(num_glyphs + 1);
+    let mut x_mins = vec![0i16; num_glyphs];
+
+    for (glyph, x_min) in x_mins.iter_mut().enumerate() {
+        offsets.push(glyf.len());
+        let contours = n_contours.i16()?;
+
+        if contours == 0 {
+            continue; // Empty glyph
+        }
+
+        if contours < 0 {
+            // Composite: components are stored verbatim, bounding box is always explicit
+            if !has_bbox(glyph) {
+                return Err("Composite glyph without bounding box".to_string());
+            }
+            let bounds = bbox.bytes(8)?;
+            let start = composite.position();
+            let mut has_instructions = false;
+            loop {
+                let component_flags = composite.u16()?;
+                composite.skip(2)?; // glyph index
+                let mut skip = if component_flags & ARG_1_AND_2_ARE_WORDS != 0 { 4 } else { 2 };
+                if component_flags & WE_HAVE_A_SCALE != 0 {
+                    skip += 2;
+                } else if component_flags & WE_HAVE_AN_X_AND_Y_SCALE != 0 {
+                    skip += 4;
+                } else if component_flags & WE_HAVE_A_TWO_BY_TWO != 0 {
+                    skip += 8;
+                }
+                composite.skip(skip)?;
+                has_instructions |= component_flags & WE_HAVE_INSTRUCTIONS != 0;
+                if component_flags & MORE_COMPONENTS == 0 {
+                    break;
+                }
+            }
+            let components = &composite.data[start..composite.position()];
+
+            glyf.extend_from_slice(&(-1i16).to_be_bytes());
+            glyf.extend_from_slice(bounds);
+            glyf.extend_from_slice(components);
+            if has_instructions {
+                let length = glyph_stream.u255()?;
+                glyf.extend_from_slice(&length.to_be_bytes());
+                glyf.extend_from_slice(instructions.bytes(length as usize)?);
+            }
+            *x_min = i16::from_be_bytes([bounds[0], bounds[1]]);
+        } else {
+            // Simple: decode point triplets into absolute coordinates
+            let mut end_points = Vec::with_capacity(contours as usize);
+            let mut total_points = 0usize;
+            for _ in 0..contours {
+                let contour_points = n_points.u255()? as usize;
+                if contour_points == 0 {
+                    return Err("Glyph contour without points".to_string());
+                }
+                total_points += contour_points;
+                end_points.push(u16::try_from(total_points - 1).map_err(|_| "Too many points in glyph")?);
+            }
+
+            let mut points: Vec<(i32, i32, bool)> = Vec::with_capacity(total_points);
+            let (mut x, mut y) = (0i32, 0i32);
+            for _ in 0..total_points {
+                let flag = flags.u8()?;
+                let (dx, dy) = decode_triplet(flag & 0x7F, &mut glyph_stream)?;
+                x += dx; // Deltas are at most 16 bits and x, y stay within i16, so this can't overflow
+                y += dy;
+                if i16::try_from(x).is_err() || i16::try_from(y).is_err() {
+                    return Err("Glyph coordinates out of range".to_string());
+                }
+                points.push((x, y, flag & 0x80 == 0));
+            }
+
+            let instruction_length = glyph_stream.u255()?;
+            let instruction_bytes = instructions.bytes(instruction_length as usize)?;
+
+            let bounds: [i16; 4] = if has_bbox(glyph) {
+                [bbox.i16()?, bbox.i16()?, bbox.i16()?, bbox.i16()?]
+            } else {
+                let min_x = points.iter().map(|p| p.0).min().unwrap_or(0);
+                let min_y = points.iter().map(|p| p.1).min().unwrap_or(0);
+                let max_x = points.iter().map(|p| p.0).max().unwrap_or(0);
+                let max_y = points.iter().map(|p| p.1).max().unwrap_or(0);
+                [min_x as i16, min_y as i16, max_x as i16, max_y as i16]
+            };
+            *x_min = bounds[0];
+
+            glyf.extend_from_slice(&contours.to_be_bytes());
+            for value in bounds {
+                glyf.extend_from_slice(&value.to_be_bytes());
+            }
+            for end in end_points {
+                glyf.extend_from_slice(&end.to_be_bytes());
+            }
+            glyf.extend_from_slice(&instruction_length.to_be_bytes());
+            glyf.extend_from_slice(instruction_bytes);
+            encode_points(&mut glyf, &points)?;
+        }
+
+        // Keep glyph offsets 4-byte aligned (valid for both loca formats)
+        while !glyf.len().is_multiple_of(4) {
+            glyf.push(0);
+        }
+    }
+    offsets.push(glyf.len());
+
+    // Short offsets (halved, 16 bits) only reach 128 KiB; the rebuilt glyf can be larger than the original
+    let long_loca = index_format != 0 || glyf.len() / 2 > u16::MAX as usize;
+    if u32::try_from(glyf.len()).is_err() {
+        return Err("glyf table too large".to_string());
+    }
+    let mut loca = Vec::with_capacity(offsets.len() * 4);
+    for offset in offsets {
+        if long_loca {
+            loca.extend_from_slice(&(offset as u32).to_be_bytes());
+        } else {
+            loca.extend_from_slice(&((offset / 2) as u16).to_be_bytes());
+        }
+    }
+
+    Ok(RebuiltGlyphs { glyf, loca, long_loca, x_mins })
+}
+
+// Helper: Decode one point delta of the WOFF2 triplet encoding
+fn decode_triplet(flag: u8, stream: &mut Reader) -> Result<(i32, i32), String> {
+    let with_sign = |flag: u8, value: i32| if flag & 1 != 0 { value } else { -value };
+    let flag_value = flag as i32;
+
+    Ok(match flag {
+        0..=9 => (0, with_sign(flag, ((flag_value & 14) << 7) + stream.u8()? as i32)),
+        10..=19 => (with_sign(flag, (((flag_value - 10) & 14) << 7) + stream.u8()? as i32), 0),
+        20..=83 => {
+            let b0 = flag_value - 20;
+            let b1 = stream.u8()? as i32;
+            (
+                with_sign(flag, 1 + (b0 & 0x30) + (b1 >> 4)),
+                with_sign(flag >> 1, 1 + ((b0 & 0x0C) << 2) + (b1 & 0x0F)),
+            )
+        }
+        84..=119 => {
+            let b0 = flag_value - 84;
+            let (b1, b2) = (stream.u8()? as i32, stream.u8()? as i32);
+            (
+                with_sign(flag, 1 + ((b0 / 12) << 8) + b1),
+                with_sign(flag >> 1, 1 + (((b0 % 12) >> 2) << 8) + b2),
+            )
+        }
+        120..=123 => {
+            let (b1, b2, b3) = (stream.u8()? as i32, stream.u8()? as i32, stream.u8()? as i32);
+            (with_sign(flag, (b1 << 4) + (b2 >> 4)), with_sign(flag >> 1, ((b2 & 0x0F) << 8) + b3))
+        }
+        _ => {
+            let (b1, b2, b3, b4) = (stream.u8()? as i32, stream.u8()? as i32, stream.u8()? as i32, stream.u8()? as i32);
+            (with_sign(flag, (b1 << 8) + b2), with_sign(flag >> 1, (b3 << 8) + b4))
+        }
+    })
+}
+
+// Helper: Write simple glyph flags and coordinate deltas in the TrueType encoding
+fn encode_points(glyf: &mut Vec<u8>, points: &[(i32, i32, bool)]) -> Result<(), String> {
+    let delta = |d: i32| i16::try_from(d).map_err(|_| "Glyph point delta out of range".to_string());
+    let mut flags = Vec::with_capacity(points.len());
+    let mut xs = Vec::new();
+    let mut ys = Vec::new();
+    let (mut last_x, mut last_y) = (0, 0);
+
+    for &(x, y, on_curve) in points {
+        let mut flag = if on_curve { ON_CURVE } else { 0 };
+        let (dx, dy) = (x - last_x, y - last_y);
+        (last_x, last_y) = (x, y);
+
+        if dx == 0 {
+            flag |= X_SAME_OR_POSITIVE;
+        } else if dx.abs() < 256 {
+            flag |= X_SHORT | if dx > 0 { X_SAME_OR_POSITIVE } else { 0 };
+            xs.push(dx.unsigned_abs() as u8);
+        } else {
+            xs.extend_from_slice(&delta(dx)?.to_be_bytes());
+        }
+
+        if dy == 0 {
+            flag |= Y_SAME_OR_POSITIVE;
+        } else if dy.abs() < 256 {
+            flag |= Y_SHORT | if dy > 0 { Y_SAME_OR_POSITIVE } else { 0 };
+            ys.push(dy.unsigned_abs() as u8);
+        } else {
+            ys.extend_from_slice(&delta(dy)?.to_be_bytes());
+        }
+
+        flags.push(flag);
+    }
+
+    glyf.extend_from_slice(&flags);
+    glyf.extend_from_slice(&xs);
+    glyf.extend_from_slice(&ys);
+    Ok(())
+}
+
+// Helper: Rebuild hmtx, deriving omitted left side bearings from glyph bounding boxes
+fn rebuild_hmtx(data: &[u8], x_mins: &[i16], num_h_metrics: usize) -> Result<Vec<u8>, String> {
+    let mut reader = Reader::new(data);
+    let flags = reader.u8()?;
+    let num_glyphs = x_mins.len();
+    let num_h_metrics = num_h_metrics.min(num_glyphs);
+
+    let mut advances = Vec::with_capacity(num_h_metrics);
+    for _ in 0..num_h_metrics {
+        advances.push(reader.u16()?);
+    }
+    let mut bearings = Vec::with_capacity(num_glyphs);
+    for (glyph, x_min) in x_mins.iter().enumerate() {
+        let explicit = if glyph < num_h_metrics { flags & 1 == 0 } else { flags & 2 == 0 };
+        bearings.push(if explicit { reader.i16()? } else { *x_min });
+    }
+
+    let mut hmtx = Vec::with_capacity(num_h_metrics * 4 + (num_glyphs - num_h_metrics) * 2);
+    for (glyph, bearing) in bearings.iter().enumerate() {
+        if glyph < num_h_metrics {
+            hmtx.extend_from_slice(&advances[glyph].to_be_bytes());
+        }
+        hmtx.extend_from_slice(&bearing.to_be_bytes());
+    }
+    Ok(hmtx)
+}
+
+// Helper: Assemble an SFNT file from tables, with directory and checksums
+fn build_sfnt(flavor: u32, mut tables: Vec<([u8; 4], Vec<u8>)>) -> Vec<u8> {
+    tables.sort_by_key(|(tag, _)| *tag);
+    let num_tables = tables.len() as u16;
+    let entry_selector = 15 - num_tables.max(1).leading_zeros() as u16;
+    let search_range = (1u16 << entry_selector) * 16;
+
+    let mut font = Vec::new();
+    font.extend_from_slice(&flavor.to_be_bytes());
+    font.extend_from_slice(&num_tables.to_be_bytes());
+    font.extend_from_slice(&search_range.to_be_bytes());
+    font.extend_from_slice(&entry_selector.to_be_bytes());
+    font.extend_from_slice(&(num_tables * 16 - search_range).to_be_bytes());
+
+    let mut offset = 12 + tables.len() * 16;
+    let mut head_offset = None;
+    for (tag, data) in tables.iter_mut() {
+        if tag == b"head" && data.len() >= 12 {
+            data[8..12].fill(0); // checkSumAdjustment is computed over the whole font below
+            head_offset = Some(offset);
+        }
+        font.extend_from_slice(tag);
+        font.extend_from_slice(&checksum(data).to_be_bytes());
+        font.extend_from_slice(&(offset as u32).to_be_bytes());
+        font.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        offset += data.len().div_ceil(4) * 4;
+    }
+    for (_, data) in &tables {
+        font.extend_from_slice(data);
+        font.resize(font.len().div_ceil(4) * 4, 0);
+    }
+
+    if let Some(head) = head_offset {
+        let adjustment = 0xB1B0_AFBAu32.wrapping_sub(checksum(&font));
+        font[head + 8..head + 12].copy_from_slice(&adjustment.to_be_bytes());
+    }
+    font
+}
+
+fn checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0u32, |sum, chunk| {
+        let mut word = [0u8; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+// Big-endian cursor over font data
+struct Reader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Reader { data, offset: 0 }
+    }
+
+    fn position(&self) -> usize {
+        self.offset
+    }
+
+    fn bytes(&mut self, length: usize) -> Result<&'a [u8], String> {
+        let bytes = self
+            .data
+            .get(self.offset..self.offset + length)
+            .ok_or("Unexpected end of WOFF2 data")?;
+        self.offset += length;
+        Ok(bytes)
+    }
+
+    fn skip(&mut self, length: usize) -> Result<(), String> {
+        self.bytes(length).map(|_| ())
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        let b = self.bytes(2)?;
+        Ok(u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    fn i16(&mut self) -> Result<i16, String> {
+        Ok(self.u16()? as i16)
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        let b = self.bytes(4)?;
+        Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn tag(&mut self) -> Result<[u8; 4], String> {
+        let b = self.bytes(4)?;
+        Ok([b[0], b[1], b[2], b[3]])
+    }
+
+    // UIntBase128: 7 bits per byte, high bit = continuation, at most 5 bytes
+    fn base128(&mut self) -> Result<u32, String> {
+        let mut value: u32 = 0;
+        for i in 0..5 {
+            let byte = self.u8()?;
+            if i == 0 && byte == 0x80 {
+                return Err("Invalid UIntBase128 value".to_string());
+            }
+            if value & 0xFE00_0000 != 0 {
+                return Err("UIntBase128 value overflow".to_string());
+            }
+            value = (value << 7) | (byte & 0x7F) as u32;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err("UIntBase128 value too long".to_string())
+    }
+
+    // 255UInt16: variable-length encoding used for point counts and instruction lengths
+    fn u255(&mut self) -> Result<u16, String> {
+        Ok(match self.u8()? {
+            253 => self.u16()?,
+            254 => self.u8()? as u16 + 506,
+            255 => self.u8()? as u16 + 253,
+            code => code as u16,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustybuzz::ttf_parser;
+
+    // Open Sans Regular (Apache 2.0, see tests/fixtures/OpenSans-LICENSE.txt); transformed glyf/loca
+    const OPEN_SANS: &[u8] = include_bytes!("../tests/fixtures/OpenSans-Regular.woff2");
+
+    fn table<'a>(font: &'a [u8], tag: &[u8; 4]) -> &'a [u8] {
+        let mut reader = Reader::new(&font[4..]);
+        let num_tables = reader.u16().unwrap() as usize;
+        reader.skip(6).unwrap();
+        for _ in 0..num_tables {
+            let entry = (reader.tag().unwrap(), reader.u32().unwrap(), reader.u32().unwrap(), reader.u32().unwrap());
+            if &entry.0 == tag {
+                let data = &font[entry.2 as usize..(entry.2 + entry.3) as usize];
+                let mut summed = data.to_vec();
+                if tag == b"head" {
+                    summed[8..12].fill(0); // The head checksum excludes checkSumAdjustment
+                }
+                assert_eq!(checksum(&summed), entry.1, "checksum of {}", String::from_utf8_lossy(tag));
+                return data;
+            }
+        }
+        panic!("missing {} table", String::from_utf8_lossy(tag));
+    }
+
+    // Helper: Transformed glyf table with one glyph and the given stream contents
+    fn transformed_glyf(n_contours: &[u8], n_points: &[u8], flags: &[u8], glyphs: &[u8]) -> Vec<u8> {
+        let bbox = [0u8; 4];
+        let streams: [&[u8]; 7] = [n_contours, n_points, flags, glyphs, &[], &bbox, &[]];
+        let mut data = vec![0, 0, 0, 0, 0, 1, 0, 0];
+        for stream in streams {
+            data.extend_from_slice(&(stream.len() as u32).to_be_bytes());
+        }
+        for stream in streams {
+            data.extend_from_slice(stream);
+        }
+        data
+    }
+
+    #[test]
+    fn decodes_fixture_into_valid_sfnt() {
+        assert!(is_woff2(OPEN_SANS));
+        let font = decode(OPEN_SANS).expect("fixture decodes");
+        assert_eq!(checksum(&font), 0xB1B0_AFBA, "head checkSumAdjustment");
+
+        let face = ttf_parser::Face::parse(&font, 0).expect("decoded font parses");
+        let num_glyphs = face.number_of_glyphs();
+        assert!(num_glyphs > 900);
+        assert_eq!(face.glyph_index('A').map(|g| face.glyph_hor_advance(g).is_some()), Some(true));
+
+        let glyf = table(&font, b"glyf");
+        let loca = table(&font, b"loca");
+        let long_loca = table(&font, b"head")[51] == 1;
+        let offset = |glyph: usize| {
+            let mut reader = Reader::new(loca);
+            if long_loca {
+                reader.skip(glyph * 4).unwrap();
+                reader.u32().unwrap() as usize
+            } else {
+                reader.skip(glyph * 2).unwrap();
+                reader.u16().unwrap() as usize * 2
+            }
+        };
+
+        let mut composites = 0;
+        for glyph in 0..num_glyphs {
+            let data = &glyf[offset(glyph as usize)..offset(glyph as usize + 1)];
+            let id = ttf_parser::GlyphId(glyph);
+            if data.is_empty() {
+                assert!(face.glyph_bounding_box(id).is_none());
+                continue;
+            }
+            let mut header = Reader::new(data);
+            let contours = header.i16().unwrap();
+            let (x_min, y_min, x_max, y_max) =
+                (header.i16().unwrap(), header.i16().unwrap(), header.i16().unwrap(), header.i16().unwrap());
+            if contours < 0 {
+                composites += 1;
+            }
+
+            let outline = face.glyph_bounding_box(id).expect("glyph has an outline");
+            let outline = (outline.x_min, outline.y_min, outline.x_max, outline.y_max);
+            assert_eq!(outline, (x_min, y_min, x_max, y_max), "bounds of glyph {}", glyph);
+            assert_eq!(face.glyph_hor_side_bearing(id), Some(x_min), "lsb of glyph {}", glyph);
+        }
+        assert!(composites > 0, "fixture exercises composite glyphs");
+    }
+
+    #[test]
+    fn rejects_contour_without_points() {
+        let data = transformed_glyf(&1i16.to_be_bytes(), &[0], &[], &[0]);
+        assert_eq!(rebuild_glyf(&data).err().as_deref(), Some("Glyph contour without points"));
+    }
+
+    // Helper: Transformed glyf table with one simple glyph of the given point flags and triplet bytes
+    fn simple_glyph(points: u16, flags: &[u8], triplets: &[u8]) -> Vec<u8> {
+        let mut n_points = vec![253];
+        n_points.extend_from_slice(&points.to_be_bytes());
+        let mut glyphs = triplets.to_vec();
+        glyphs.push(0); // No instructions
+        transformed_glyf(&1i16.to_be_bytes(), &n_points, flags, &glyphs)
+    }
+
+    #[test]
+    fn switches_to_long_loca_past_128_kib() {
+        // 50 000 points alternating by (+1, +1) and (-1, -1): 3 bytes each once rebuilt
+        let points = 50_000u16;
+        let flags: Vec<u8> = (0..points).map(|i| if i % 2 == 0 { 23 } else { 20 }).collect();
+        let glyphs = rebuild_glyf(&simple_glyph(points, &flags, &vec![0; points as usize])).expect("glyph rebuilds");
+
+        assert!(glyphs.glyf.len() > 2 * u16::MAX as usize);
+        assert!(glyphs.long_loca);
+        assert_eq!(glyphs.loca.len(), 8);
+        assert_eq!(glyphs.loca[4..], (glyphs.glyf.len() as u32).to_be_bytes());
+    }
+
+    #[test]
+    fn rejects_out_of_range_points() {
+        // x = -30000, then +60000: both coordinates fit in i16, the delta between them doesn't
+        let data = simple_glyph(2, &[124, 125], &[0x75, 0x30, 0, 0, 0xEA, 0x60, 0, 0]);
+        assert_eq!(rebuild_glyf(&data).err().as_deref(), Some("Glyph point delta out of range"));
+
+        // x = +40000
+        let data = simple_glyph(1, &[125], &[0x9C, 0x40, 0, 0]);
+        assert_eq!(rebuild_glyf(&data).err().as_deref(), Some("Glyph coordinates out of range"));
+    }
+
+    #[test]
+    fn rejects_truncated_input() {
+        assert!(decode(&OPEN_SANS[..OPEN_SANS.len() / 2]).is_err());
+        assert!(decode(b"wOF2").is_err());
+        assert!(!is_woff2(b"OTTO"));
+    }
+}